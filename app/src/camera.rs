@@ -1,3 +1,5 @@
+use super::util;
+
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 #[derive(Debug)]
@@ -5,6 +7,48 @@ pub struct CameraUniform {
     pub eye: [f32; 4],
     pub view: [[f32; 4]; 4],
     pub proj: [[f32; 4]; 4],
+    // width and height of the surface in physical pixels
+    pub viewport: [f32; 4],
+}
+
+// a programmatic camera movement between two orientations
+#[derive(Clone, Copy)]
+struct Flight {
+    from: [f32; 3],
+    to: [f32; 3],
+    elapsed: f32,
+    duration: f32,
+}
+
+impl Flight {
+    // cubic ease-in-out
+    fn ease(t: f32) -> f32 {
+        if t < 0.5 {
+            4. * t * t * t
+        } else {
+            1. - (-2. * t + 2.).powi(3) * 0.5
+        }
+    }
+}
+
+// camera movements bound to held keys
+#[derive(Clone, Copy)]
+enum KeyNav { Up, Down, Left, Right, In, Out }
+
+impl KeyNav {
+    fn from_code(code: backend::event::KeyCode) -> Option<Self> {
+        use backend::event::KeyCode;
+
+        match code {
+            KeyCode::ArrowUp | KeyCode::KeyW => Some(Self::Up),
+            KeyCode::ArrowDown | KeyCode::KeyS => Some(Self::Down),
+            KeyCode::ArrowLeft | KeyCode::KeyA => Some(Self::Left),
+            KeyCode::ArrowRight | KeyCode::KeyD => Some(Self::Right),
+            KeyCode::Equal | KeyCode::NumpadAdd | KeyCode::PageUp => Some(Self::In),
+            KeyCode::Minus | KeyCode::NumpadSubtract | KeyCode::PageDown => Some(Self::Out),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -19,7 +63,23 @@ pub struct Camera {
     vertical_fov: f32,
     far_plane: f32,
     dragging: bool,
+    // distance the mouse has travelled since the last press
+    drag_travel: f32,
+    // rotation applied by dragging since the last update
+    drag_delta: [f32; 2],
+    // when the drag last moved the globe
+    drag_moved: Option<backend::chrono::DateTime<backend::chrono::Local>>,
+    // angular velocity of pitch and yaw in radians per second
+    velocity: [f32; 2],
+    // the distance zoom is easing towards
+    target_distance: f32,
+    // indexed by KeyNav
+    keys_held: [bool; 6],
+    // the finger rotating the globe and its last position
+    touch: Option<(u64, backend::Position)>,
+    touch_count: usize,
     scrolling: bool,
+    flight: Option<Flight>,
 }
 
 impl Camera {
@@ -28,57 +88,106 @@ impl Camera {
     const MULT_MIN: f32 = 1.1;
     const MULT_MAX: f32 = 1.666667;
 
+    // mouse travel below which a press and release count as a click
+    const CLICK_TOLERANCE: f32 = 4.;
+
+    // rate at which angular velocity decays after a drag
+    const FRICTION: f32 = 4.;
+    // angular velocity below which the globe stops spinning
+    const VELOCITY_MIN: f32 = 0.01;
+    // milliseconds a drag can rest before its release no longer flings the globe
+    const FLING_THRESHOLD: i64 = 50;
+
+    // rate at which the distance approaches its target
+    const ZOOM_RATE: f32 = 12.;
+    // fraction of the globe radius at which zoom snaps to its target
+    const ZOOM_EPSILON: f32 = 0.0005;
+
+    // radians per second at the initial distance
+    const KEY_ROTATION_SPEED: f32 = 1.2;
+    // relative change in distance per second
+    const KEY_ZOOM_SPEED: f32 = 0.6;
+
+    // seconds taken to return to the initial view
+    const RESET_DURATION: f32 = 1.;
+
     pub fn new(globe_radius: f32) -> Self {
         let distance = globe_radius * Self::MULT_DIST;
 
-        Self {
+        let mut camera = Self {
             distance,
             globe_radius,
             pitch: 0.,
             yaw: 0.,
-            eye: [0., 0., distance * -1.],
+            eye: [0., 0., -distance],
             target: [0.; 3],
             up: [0., 1., 0.],
             vertical_fov: std::f32::consts::PI / 2.,
             far_plane: distance * 2.,
             dragging: false,
+            drag_travel: 0.,
+            drag_delta: [0.; 2],
+            drag_moved: None,
+            velocity: [0.; 2],
+            target_distance: distance,
+            keys_held: [false; 6],
+            touch: None,
+            touch_count: 0,
             scrolling: false,
-        }
+            flight: None,
+        };
+
+        camera.reposition();
+        camera
     }
 
     pub fn movement_in_progress(&self) -> bool {
-        self.dragging || self.scrolling
+        let Self { 
+            dragging, 
+            scrolling, 
+            flight, 
+            velocity, 
+            distance, 
+            target_distance, 
+            keys_held, .. 
+        } = self;
+
+        *dragging || *scrolling || flight.is_some() || keys_held.contains(&true) || //
+            *velocity != [0.; 2] || distance != target_distance
+    }
+
+    // true if the last press was released without rotating the globe
+    pub fn clicked(&self) -> bool {
+        !self.dragging && self.drag_travel < Self::CLICK_TOLERANCE
     }
 
-    pub fn handle_event(&mut self, event: backend::AppEvent) -> bool {
-        let mult = ultraviolet::Vec3::from(self.eye).mag().abs() / //
-            self.globe_radius;
+    pub fn handle_event(
+        &mut self, 
+        event: backend::AppEvent, 
+        screen_resolution: backend::Size,
+    ) -> bool {
+        // zoom limits are checked against the target so they hold mid-animation
+        let mult = self.target_distance / self.globe_radius;
 
         let mult = (mult - Self::MULT_MIN) / //
             (Self::MULT_MAX - Self::MULT_MIN) + Self::MULT_MIN - 1.;
 
-        match event {
+        let changed = match event {
             backend::AppEvent::Mouse { 
                 button: backend::event::MouseButton::Left, 
                 state, ..
-            } => {
-                let temp = self.dragging;
-
-                self.dragging = matches!(
-                    state, backend::event::ElementState::Pressed
-                );
-                
-                self.dragging != temp
-            },
+            } => self.set_dragging(matches!(state, backend::event::ElementState::Pressed)),
             backend::AppEvent::MouseScroll { delta, .. } => {
                 let lower = delta < 0. && mult > 0.0;
                 let upper = delta > 0. && mult < 1.0;
 
                 if lower || upper {
+                    self.flight = None;
+
                     let mult = std::f32::consts::E.powf(mult);
                     let mult = mult * self.globe_radius * 0.01;
 
-                    self.distance += delta * mult;
+                    self.target_distance += delta * mult;
 
                     self.scrolling = true;
 
@@ -87,28 +196,354 @@ impl Camera {
                     false
                 }
             },
+            backend::AppEvent::Key { 
+                code: backend::event::KeyCode::Home, 
+                state: backend::event::ElementState::Pressed,
+            } => {
+                let distance = self.globe_radius * Self::MULT_DIST;
+
+                self.fly(0., 0., distance, Self::RESET_DURATION);
+
+                true
+            },
+            backend::AppEvent::Key { code, state } => match KeyNav::from_code(code) {
+                Some(nav) => {
+                    let held = matches!(state, backend::event::ElementState::Pressed);
+
+                    // key repeats are ignored, motion continues until release
+                    let temp = std::mem::replace(&mut self.keys_held[nav as usize], held);
+
+                    if held {
+                        self.flight = None;
+                        self.velocity = [0.; 2];
+                    }
+
+                    temp != held
+                }, None => false,
+            },
             backend::AppEvent::MouseScrollStopped => {
                 self.scrolling = false;
 
                 true
             },
             backend::AppEvent::MouseMotion { x, y } => {
-                if self.dragging {
-                    let mult = (((mult + 1.).ln()) * 0.0015).abs();
+                if self.dragging && self.touch.is_none() { self.drag(x, y, mult); }
 
-                    self.pitch -= y * mult;
-                    self.pitch = self.pitch.clamp(
-                        -1.0 * std::f32::consts::PI / 2. + f32::EPSILON, 
-                        std::f32::consts::PI / 2. - f32::EPSILON,
-                    );
+                self.dragging
+            },
+            backend::AppEvent::TouchStart { id, cursor } => {
+                self.touch_count += 1;
 
-                    self.yaw -= x * mult;
+                if self.touch_count == 1 {
+                    self.touch = Some((id, cursor));
+                    self.set_dragging(true)
+                } else {
+                    // a second finger turns the gesture into a pinch
+                    self.touch = None;
+                    self.velocity = [0.; 2];
+                    self.set_dragging(false)
                 }
+            },
+            backend::AppEvent::TouchMove { id, cursor } => match self.touch {
+                Some((temp, prev)) if temp == id => {
+                    self.touch = Some((id, cursor));
 
-                self.dragging
+                    self.drag(cursor.x - prev.x, cursor.y - prev.y, mult);
+
+                    true
+                }, _ => false,
+            },
+            backend::AppEvent::TouchEnd { id, .. } => {
+                self.touch_count = self.touch_count.saturating_sub(1);
+
+                // lifting one finger of a pinch doesn't resume rotation
+                match self.touch {
+                    Some((temp, _)) if temp == id => {
+                        self.touch = None;
+                        self.set_dragging(false)
+                    }, _ => false,
+                }
+            },
+            backend::AppEvent::Pinch { scale, center } if scale > 0. => {
+                let (lower, upper) = self.zoom_bounds();
+
+                self.flight = None;
+
+                // spreading the fingers moves the camera closer
+                let distance = (self.target_distance / scale).clamp(lower, upper);
+
+                self.zoom_towards(center, distance, screen_resolution);
+
+                true
             },
             _ => false,
+        };
+
+        if changed { self.reposition(); }
+
+        changed
+    }
+
+    // returns true if the state changed
+    fn set_dragging(&mut self, dragging: bool) -> bool {
+        let temp = std::mem::replace(&mut self.dragging, dragging);
+
+        if dragging {
+            self.drag_travel = 0.;
+            self.drag_delta = [0.; 2];
+            self.velocity = [0.; 2];
+
+            // user input takes precedence over flights
+            self.flight = None;
+        } else if temp {
+            let resting = self.drag_moved.take().is_none_or(|timestamp| {
+                backend::chrono::Local::now()
+                    .signed_duration_since(timestamp)
+                    .num_milliseconds() > Self::FLING_THRESHOLD
+            });
+
+            // a drag held still before release leaves the globe where it is
+            if resting {
+                self.drag_delta = [0.; 2];
+                self.velocity = [0.; 2];
+            }
         }
+
+        dragging != temp
+    }
+
+    // rotates the globe by a drag of `x` and `y` pixels
+    fn drag(&mut self, x: f32, y: f32, mult: f32) {
+        self.drag_travel += x.abs() + y.abs();
+        self.drag_moved = Some(backend::chrono::Local::now());
+
+        let mult = (((mult + 1.).ln()) * 0.0015).abs();
+
+        self.drag_delta[0] -= y * mult;
+        self.drag_delta[1] -= x * mult;
+
+        self.rotate(-y * mult, -x * mult);
+    }
+
+    // zooms to `distance` while keeping the surface under `cursor` in place
+    fn zoom_towards(
+        &mut self, 
+        cursor: backend::Position, 
+        distance: f32, 
+        screen_resolution: backend::Size,
+    ) {
+        let altitude = self.target_distance - self.globe_radius;
+
+        if let Some(anchor) = self.surface_under(cursor, screen_resolution) {
+            let (pitch, yaw) = Self::orientation(anchor);
+
+            // the view centre moves towards the anchor by the share of altitude given up
+            let t = 1. - (distance - self.globe_radius) / altitude.max(f32::EPSILON);
+
+            let yaw = (yaw - self.yaw + std::f32::consts::PI)
+                .rem_euclid(std::f32::consts::PI * 2.) - std::f32::consts::PI;
+
+            self.rotate((pitch - self.pitch) * t, yaw * t);
+        }
+
+        self.target_distance = distance;
+    }
+
+    // the point on the globe seen through `cursor`, if any
+    fn surface_under(
+        &self, 
+        cursor: backend::Position, 
+        screen_resolution: backend::Size,
+    ) -> Option<ultraviolet::Vec3> {
+        let CameraUniform { 
+            view, 
+            proj, .. 
+        } = self.build_camera_uniform(screen_resolution);
+
+        let ray = ultraviolet::Vec3::from(util::cursor_to_world_ray(
+            view, 
+            proj, 
+            util::cursor_to_ndc(cursor, screen_resolution),
+        ));
+
+        let eye = ultraviolet::Vec3::from(self.eye);
+
+        // nearest root of |eye + ray * t| = globe_radius
+        let b = eye.dot(ray);
+        let c = eye.mag_sq() - self.globe_radius * self.globe_radius;
+
+        let discriminant = b * b - c;
+
+        if discriminant < 0. { return None; }
+
+        let t = -b - discriminant.sqrt();
+
+        (t > 0.).then(|| eye + ray * t)
+    }
+
+    // the pitch and yaw that put `vertex` straight below the camera
+    fn orientation(vertex: ultraviolet::Vec3) -> (f32, f32) {
+        use std::f32::consts::PI;
+
+        let vertex = vertex.normalized();
+
+        let pitch = vertex.y.asin().clamp(-PI / 2. + f32::EPSILON, PI / 2. - f32::EPSILON);
+
+        (pitch, vertex.x.atan2(vertex.z))
+    }
+
+    // the range of distances that can be reached by zooming
+    fn zoom_bounds(&self) -> (f32, f32) {
+        let distance = |mult: f32| self.globe_radius * //
+            ((mult + 1. - Self::MULT_MIN) * (Self::MULT_MAX - Self::MULT_MIN) + Self::MULT_MIN);
+
+        (distance(0.), distance(1.))
+    }
+
+    fn rotate(&mut self, pitch: f32, yaw: f32) {
+        self.pitch = (self.pitch + pitch).clamp(
+            -std::f32::consts::PI / 2. + f32::EPSILON, 
+            std::f32::consts::PI / 2. - f32::EPSILON,
+        );
+
+        self.yaw += yaw;
+    }
+
+    // the distance at which the given extent fits on screen
+    pub fn framing_distance(&self, extent: f32) -> f32 {
+        let (lower, upper) = self.zoom_bounds();
+
+        let distance = self.globe_radius + extent * 0.5 / (self.vertical_fov * 0.5).tan();
+
+        distance.clamp(lower, upper)
+    }
+
+    // degrees of latitude covered by a pixel on the surface straight below the camera
+    pub fn degrees_per_pixel(&self, screen_resolution: backend::Size) -> f32 {
+        let height = (self.distance - self.globe_radius) * (self.vertical_fov * 0.5).tan() * 2.;
+
+        (height / (screen_resolution.height.max(1) as f32 * self.globe_radius)).to_degrees()
+    }
+
+    // animates the camera towards a position given in degrees
+    // the movement is eased over `duration` seconds
+    pub fn fly_to(&mut self, lat: f32, lon: f32, distance: f32, duration: f32) {
+        let target = ultraviolet::Vec3::from(util::lat_lon_to_vertex([lat, lon], 1.));
+
+        let (pitch, yaw) = Self::orientation(target);
+
+        self.fly(pitch, yaw, distance, duration);
+    }
+
+    fn fly(&mut self, pitch: f32, yaw: f32, distance: f32, duration: f32) {
+        use std::f32::consts::PI;
+
+        // take the shorter way around the globe
+        let yaw = self.yaw + (yaw - self.yaw + PI).rem_euclid(PI * 2.) - PI;
+
+        let (lower, upper) = self.zoom_bounds();
+
+        let distance = distance.clamp(lower, upper);
+
+        self.velocity = [0.; 2];
+
+        self.flight = Some(Flight {
+            from: [self.pitch, self.yaw, self.distance],
+            to: [pitch, yaw, distance],
+            elapsed: 0.,
+            duration: duration.max(f32::EPSILON),
+        });
+    }
+
+    // progresses flights, momentum and zoom by `dt` seconds
+    // returns false once the camera has come to rest
+    pub fn update(&mut self, dt: f32) -> bool {
+        let mut animating = false;
+
+        if let Some(flight) = self.flight.as_mut() {
+            flight.elapsed = (flight.elapsed + dt).min(flight.duration);
+
+            let t = Flight::ease(flight.elapsed / flight.duration);
+
+            let Flight { from, to, elapsed, duration } = *flight;
+
+            self.pitch = from[0] + (to[0] - from[0]) * t;
+            self.yaw = from[1] + (to[1] - from[1]) * t;
+            self.distance = from[2] + (to[2] - from[2]) * t;
+            self.target_distance = self.distance;
+
+            if elapsed >= duration { self.flight = None; }
+
+            animating = true;
+        }
+
+        if self.dragging {
+            // estimate the velocity from the motion since the last frame
+            let [pitch, yaw] = std::mem::take(&mut self.drag_delta);
+
+            let dt = dt.max(f32::EPSILON);
+
+            self.velocity = [pitch / dt, yaw / dt];
+
+            animating |= self.velocity != [0.; 2];
+        } else if self.velocity != [0.; 2] {
+            let [pitch, yaw] = self.velocity;
+
+            self.rotate(pitch * dt, yaw * dt);
+
+            let decay = (-Self::FRICTION * dt).exp();
+
+            self.velocity = [pitch * decay, yaw * decay];
+
+            if ultraviolet::Vec2::from(self.velocity).mag() < Self::VELOCITY_MIN {
+                self.velocity = [0.; 2];
+            }
+
+            animating = true;
+        }
+
+        let held = |nav: KeyNav| if self.keys_held[nav as usize] { 1. } else { 0. };
+
+        let pitch = held(KeyNav::Down) - held(KeyNav::Up);
+        let yaw = held(KeyNav::Right) - held(KeyNav::Left);
+        let zoom = held(KeyNav::Out) - held(KeyNav::In);
+
+        if pitch != 0. || yaw != 0. {
+            // rotate more slowly when close to the surface
+            let mult = (self.distance - self.globe_radius) / //
+                (self.globe_radius * (Self::MULT_DIST - 1.));
+
+            let speed = Self::KEY_ROTATION_SPEED * mult * dt;
+
+            self.rotate(pitch * speed, yaw * speed);
+
+            animating = true;
+        }
+
+        if zoom != 0. {
+            let (lower, upper) = self.zoom_bounds();
+
+            self.target_distance = (self.target_distance * (zoom * Self::KEY_ZOOM_SPEED * dt).exp())
+                .clamp(lower, upper);
+
+            animating = true;
+        }
+
+        if self.distance != self.target_distance {
+            let t = 1. - (-Self::ZOOM_RATE * dt).exp();
+
+            self.distance += (self.target_distance - self.distance) * t;
+
+            if (self.target_distance - self.distance).abs() < self.globe_radius * Self::ZOOM_EPSILON {
+                self.distance = self.target_distance;
+            }
+
+            animating = true;
+        }
+
+        if animating { self.reposition(); }
+
+        animating
     }
 
     pub fn build_camera_uniform(&self, screen_resolution: backend::Size) -> CameraUniform {
@@ -117,9 +552,15 @@ impl Camera {
             target,
             up, 
             vertical_fov: fovy,
-            far_plane: zfar, ..
+            far_plane: zfar, 
+            distance,
+            globe_radius, ..
         } = self;
 
+        // the near plane follows the surface to keep depth precision near the globe
+        // gl projections map the near plane to -1, so only half the gap is usable
+        let znear = ((distance - globe_radius) * 0.25).max(0.1);
+
         let view = ultraviolet::Mat4::look_at(
             ultraviolet::Vec3::from(eye),
             ultraviolet::Vec3::from(target),
@@ -131,7 +572,7 @@ impl Camera {
         let proj = ultraviolet::projection::rh_ydown::perspective_gl(
             *fovy,
             width as f32 / height as f32,
-            0.1,
+            znear,
             *zfar,
         );
 
@@ -143,10 +584,11 @@ impl Camera {
             proj: proj
                 .as_component_array()
                 .map(|ultraviolet::Vec4 { x, y, z, w }| [x, y, z, w]),
+            viewport: [width as f32, height as f32, 0., 0.],
         }
     }
 
-    pub fn update(&mut self) -> &Self {
+    fn reposition(&mut self) {
         fn calculate_cartesian_eye_position(
             pitch: f32, 
             yaw: f32, 
@@ -164,7 +606,5 @@ impl Camera {
             self.yaw, 
             self.distance
         ).into();
-
-        self
     }
 }
\ No newline at end of file
@@ -0,0 +1,235 @@
+use backend::wgpu as wgpu;
+
+// displays the properties of the picked feature in the top right corner
+pub struct FeatureInfo {
+    font_system: glyphon::FontSystem,
+    font_attrs: glyphon::Attrs<'static>,
+    swash_cache: glyphon::SwashCache,
+    atlas: glyphon::TextAtlas,
+    buffer: glyphon::Buffer,
+    renderer: glyphon::TextRenderer,
+    visible: bool,
+    screen_resolution: backend::Size,
+}
+
+impl FeatureInfo {
+    const METRICS: glyphon::Metrics = glyphon::Metrics::new(20., 24.);
+
+    const WIDTH: f32 = 320.;
+    const MARGIN: f32 = 16.;
+
+    // property keys paired with their display names
+    const PROPERTIES: &'static [(&'static str, &'static str)] = &[
+        ("NAME", "Name"),
+        ("SUBJECTO", "Subject to"),
+        ("PARTOF", "Part of"),
+        ("BORDERPRECISION", "Border precision"),
+    ];
+
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        surface_format: wgpu::TextureFormat,
+        font_bytes: std::sync::Arc<Vec<u8>>,
+        font_family: &'static str,
+    ) -> Self {
+        let mut font_system = glyphon::FontSystem::new_with_fonts({
+            use glyphon::fontdb::Source;
+
+            Some(Source::Binary(font_bytes))
+        });
+
+        let mut atlas = glyphon::TextAtlas::new(
+            device,
+            queue,
+            surface_format
+        );
+
+        let buffer = glyphon::Buffer::new(&mut font_system, Self::METRICS);
+
+        let renderer = glyphon::TextRenderer::new(
+            &mut atlas,
+            device,
+            wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            }, None
+        );
+
+        Self {
+            font_system,
+            font_attrs: glyphon::Attrs::new().family(glyphon::Family::Name(font_family)),
+            swash_cache: glyphon::SwashCache::new(),
+            atlas,
+            buffer,
+            renderer,
+            visible: false,
+            screen_resolution: backend::Size::default(),
+        }
+    }
+
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        screen_resolution: backend::Size,
+    ) {
+        self.screen_resolution = screen_resolution;
+
+        self.refresh(device, queue);
+    }
+
+    // hides the panel when no feature is given
+    pub fn show(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        entry: Option<&geojson::JsonObject>,
+    ) {
+        let text = entry.map(|entry| {
+            let mut text = String::new();
+
+            for (key, name) in Self::PROPERTIES.iter() {
+                let value = match entry.get(*key) {
+                    Some(geojson::JsonValue::String(value)) => value.to_owned(),
+                    Some(geojson::JsonValue::Number(value)) => value.to_string(),
+                    _ => String::from("unknown"),
+                };
+
+                text.push_str(&format!("{name}: {value}\n"));
+            }
+
+            text
+        });
+
+        self.set_text(device, queue, text);
+    }
+
+    // markers are described by their date range rather than their borders
+    pub fn show_marker(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        entry: &geojson::JsonObject,
+    ) {
+        use super::timeline;
+
+        let value = |key: &str| match entry.get(key) {
+            Some(geojson::JsonValue::String(value)) => Some(value.to_owned()),
+            Some(geojson::JsonValue::Number(value)) => Some(value.to_string()),
+            _ => None,
+        };
+
+        let year = |key: &str| value(key)
+            .and_then(|year| year.parse::<i32>().ok())
+            .map(timeline::format_year);
+
+        let years = match (year("START"), year("END")) {
+            (Some(start), Some(end)) if start == end => start,
+            (Some(start), Some(end)) => format!("{start} to {end}"),
+            (Some(start), None) => format!("from {start}"),
+            _ => String::from("unknown"),
+        };
+
+        let mut text = String::new();
+
+        text.push_str(&format!("Name: {}\n", value("NAME").unwrap_or(String::from("unknown"))));
+        text.push_str(&format!("Kind: {}\n", value("KIND").unwrap_or(String::from("unknown"))));
+        text.push_str(&format!("Date: {years}\n"));
+
+        if let Some(description) = value("DESCRIPTION") {
+            text.push_str(&format!("\n{description}\n"));
+        }
+
+        self.set_text(device, queue, Some(text));
+    }
+
+    fn set_text(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        text: Option<String>,
+    ) {
+        let Self { font_system, font_attrs, buffer, visible, .. } = self;
+
+        *visible = text.is_some();
+
+        if let Some(text) = text {
+            #[allow(unused_parens)]
+            buffer.set_text(
+                font_system,
+                text.trim_end(),
+                (*font_attrs),
+                glyphon::Shaping::Basic,
+            );
+        }
+
+        self.refresh(device, queue);
+    }
+
+    fn refresh(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        #[allow(unused_variables)]
+        if let Err(e) = self.prepare(device, queue) {
+            self.atlas.trim();
+
+            #[cfg(feature = "logging")]
+            backend::log::debug!("Failed to prepare feature info panel.\n{e}");
+        }
+    }
+
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<(), glyphon::PrepareError> {
+        let Self {
+            font_system,
+            swash_cache,
+            atlas,
+            buffer,
+            renderer,
+            visible,
+            screen_resolution: backend::Size { width, height }, ..
+        } = self;
+
+        if !*visible { return Ok(()); }
+
+        buffer.set_size(font_system, Self::WIDTH, *height as f32);
+        buffer.set_wrap(font_system, glyphon::Wrap::Word);
+        buffer.shape_until_scroll(font_system);
+
+        let region = glyphon::TextArea {
+            buffer,
+            left: (*width as f32 - Self::WIDTH - Self::MARGIN).max(0.),
+            top: Self::MARGIN,
+            scale: 1.,
+            bounds: glyphon::TextBounds {
+                left: 0,
+                top: 0,
+                right: *width as i32,
+                bottom: *height as i32,
+            },
+            default_color: glyphon::Color::rgb(255, 255, 255),
+        };
+
+        renderer.prepare(
+            device,
+            queue,
+            font_system,
+            atlas,
+            glyphon::Resolution { width: *width, height: *height },
+            Some(region),
+            swash_cache,
+        )
+    }
+
+    pub fn render<'p, 'a: 'p>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'p>,
+    ) -> Result<(), glyphon::RenderError> {
+        let Self { atlas, renderer, visible, .. } = self;
+
+        if *visible { renderer.render(atlas, pass) } else { Ok(()) }
+    }
+}
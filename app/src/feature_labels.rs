@@ -17,7 +17,7 @@ struct LabelBuffer {
 }
 
 impl LabelBuffer {
-    fn as_text_area(&self) -> glyphon::TextArea {
+    fn as_text_area(&self) -> glyphon::TextArea<'_> {
         let Self { 
             buffer, 
             bounds: glyphon::TextBounds {
@@ -119,7 +119,7 @@ impl LabelEngine {
         let camera::CameraUniform {
             eye,
             view,
-            proj,
+            proj, ..
         } = camera_uniform;
 
         let maxima_sq = util::hemisphere_maxima_sq(eye, globe_radius);
@@ -177,7 +177,7 @@ impl LabelEngine {
         for Label { text, pos, color, feature_area } in visible_feature_labels.drain(0..) {
             let pos = [
                 (pos[0] + 1.) * 0.5 * width as f32,
-                (pos[1] * -1. + 1.) * 0.5 * height as f32,
+                (1. - pos[1]) * 0.5 * height as f32,
             ];
 
             let mut buffer = glyphon::Buffer::new(font_system, Self::METRICS);
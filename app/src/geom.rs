@@ -1,6 +1,8 @@
 use backend::wgpu as wgpu;
 
-use super::util;
+use super::{style, util};
+
+use std::{collections, ops, sync};
 
 pub struct Geometry<T: bytemuck::Pod + bytemuck::Zeroable, M: Default> {
     #[allow(dead_code)]
@@ -12,32 +14,6 @@ pub struct Geometry<T: bytemuck::Pod + bytemuck::Zeroable, M: Default> {
 }
 
 impl<T: bytemuck::Pod + bytemuck::Zeroable, M: Default> Geometry<T, M> {
-    pub fn empty(device: &wgpu::Device) -> Self {
-        use std::mem;
-
-        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: None,
-            size: mem::size_of::<T>() as u64,
-            usage: wgpu::BufferUsages::VERTEX,
-            mapped_at_creation: false,
-        });
-    
-        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: None,
-            size: 4u64,
-            usage: wgpu::BufferUsages::INDEX,
-            mapped_at_creation: false,
-        });
-
-        Self {
-            vertices: Vec::with_capacity(0),
-            vertex_buffer,
-            indices: Vec::with_capacity(0),
-            index_buffer,
-            metadata: M::default(),
-        }
-    }
-
     pub fn destroy(self) {
         let Self { vertex_buffer, index_buffer, .. } = self;
 
@@ -72,11 +48,127 @@ impl GlobeVertex {
 pub struct FeatureVertex { 
     pub pos: [f32; 3],
     pub color: [f32; 3],
+    // index into FeatureMetadata::entries
+    pub feature: u32,
+    // see border_precision
+    pub precision: u32,
 }
 
 impl FeatureVertex {
     const VERTEX_ATTRIBUTES: &'static [wgpu::VertexAttribute] = &{
-        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3]
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Uint32, 3 => Uint32]
+    };
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        use std::mem;
+
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: Self::VERTEX_ATTRIBUTES,
+        }
+    }
+}
+
+// one corner of a quad spanning a border segment
+// the quad is widened to a constant screen-space width in the vertex shader
+// its winding follows the segment's direction, so these quads are drawn without culling
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct OutlineVertex {
+    pub start: [f32; 3],
+    pub end: [f32; 3],
+    // which end of the segment this corner belongs to and which side of it
+    pub corner: [f32; 2],
+    // degrees travelled along the ring, used to dash uncertain borders
+    pub distance: f32,
+    // index into FeatureMetadata::entries
+    pub feature: u32,
+    // see border_precision
+    pub precision: u32,
+}
+
+impl OutlineVertex {
+    const VERTEX_ATTRIBUTES: &'static [wgpu::VertexAttribute] = &{
+        wgpu::vertex_attr_array![
+            0 => Float32x3, 
+            1 => Float32x3, 
+            2 => Float32x2, 
+            3 => Float32, 
+            4 => Uint32, 
+            5 => Uint32
+        ]
+    };
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        use std::mem;
+
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: Self::VERTEX_ATTRIBUTES,
+        }
+    }
+}
+
+// one corner of a billboard centered on a point of interest
+// the billboard is sized in screen space in the vertex shader
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MarkerVertex {
+    pub center: [f32; 3],
+    pub corner: [f32; 2],
+    pub color: [f32; 3],
+    // index into markers::Markers
+    pub marker: u32,
+    // first and last year the marker is shown
+    pub years: [i32; 2],
+}
+
+impl MarkerVertex {
+    const VERTEX_ATTRIBUTES: &'static [wgpu::VertexAttribute] = &{
+        wgpu::vertex_attr_array![
+            0 => Float32x3, 
+            1 => Float32x2, 
+            2 => Float32x3, 
+            3 => Uint32, 
+            4 => Sint32x2
+        ]
+    };
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        use std::mem;
+
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: Self::VERTEX_ATTRIBUTES,
+        }
+    }
+}
+
+// one corner of a quad spanning a segment of a route
+// widened to a constant screen-space width like OutlineVertex
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct RouteVertex {
+    pub start: [f32; 3],
+    pub end: [f32; 3],
+    pub corner: [f32; 2],
+    // degrees travelled from the start of the route, animates the arrows
+    pub distance: f32,
+    pub color: [f32; 3],
+}
+
+impl RouteVertex {
+    const VERTEX_ATTRIBUTES: &'static [wgpu::VertexAttribute] = &{
+        wgpu::vertex_attr_array![
+            0 => Float32x3, 
+            1 => Float32x3, 
+            2 => Float32x2, 
+            3 => Float32, 
+            4 => Float32x3
+        ]
     };
 
     pub fn layout() -> wgpu::VertexBufferLayout<'static> {
@@ -99,11 +191,28 @@ pub struct BoundingBox {
     pub br: [f32; 3],
 }
 
+// confidence in a feature's borders, from 1 (approximate) to 3 (legally defined)
+// missing or unrecognized values are treated as approximate
+pub fn border_precision(metadata: &geojson::JsonObject) -> u32 {
+    let precision = match metadata.get("BORDERPRECISION") {
+        Some(geojson::JsonValue::Number(precision)) => precision.as_u64(),
+        Some(geojson::JsonValue::String(precision)) => precision.parse().ok(),
+        _ => None,
+    };
+
+    precision.unwrap_or(1).clamp(1, 3) as u32
+}
+
 #[derive(Default)]
 pub struct FeatureMetadata {
-    pub entries: Vec<geojson::JsonObject>,
+    // properties of each feature, shared by every level of detail
+    pub entries: sync::Arc<Vec<geojson::JsonObject>>,
     pub colors: Vec<[u8; 3]>,
     pub bounding_boxes: Vec<(BoundingBox, usize)>,
+    // span of each polygon within the geometry's indices
+    pub index_ranges: Vec<(ops::Range<u32>, usize)>,
+    // sphere enclosing each polygon, parallel to index_ranges
+    pub bounding_spheres: Vec<([f32; 3], f32)>,
 }
 
 impl Geometry<GlobeVertex, ()> {
@@ -138,7 +247,7 @@ impl Geometry<GlobeVertex, ()> {
         }
     
         vertices.push(GlobeVertex { 
-            pos: [0., globe_radius * -1., 0.] 
+            pos: [0., -globe_radius, 0.] 
         });
     
         let v0 = 0;
@@ -196,16 +305,125 @@ impl Geometry<GlobeVertex, ()> {
     }
 }
 
-impl Geometry<FeatureVertex, FeatureMetadata> {
-    pub fn build_feature_geometry(
-        device: &wgpu::Device,
-        features: &[geojson::Feature],
+// a feature set's fills and outlines at one level of detail
+pub type FeatureLevel = (Geometry<FeatureVertex, FeatureMetadata>, Geometry<OutlineVertex, ()>);
+
+// how far in degrees simplified borders may stray from the originals, finest level first
+const LOD_TOLERANCES: [f64; 4] = [0., 0.01, 0.03, 0.1];
+
+// a level is drawn once its error covers no more than this many pixels
+const LOD_PIXELS: f32 = 1.5;
+
+// the coarsest level of detail that looks the same at the given scale
+pub fn lod_level(degrees_per_pixel: f32) -> usize {
+    LOD_TOLERANCES
+        .iter()
+        .rposition(|tolerance| *tolerance as f32 <= degrees_per_pixel * LOD_PIXELS)
+        .unwrap_or(0)
+}
+
+// fills and outlines of one level of detail, ready to be moved to the gpu
+// building them doesn't need the gpu, so it can happen away from the event loop
+pub struct FeatureMesh {
+    vertices: Vec<FeatureVertex>,
+    indices: Vec<u32>,
+    metadata: FeatureMetadata,
+    outline_vertices: Vec<OutlineVertex>,
+    outline_indices: Vec<u32>,
+}
+
+impl FeatureMesh {
+    pub fn upload(self, device: &wgpu::Device) -> FeatureLevel {
+        use wgpu::util::DeviceExt as _;
+
+        let Self {
+            vertices,
+            indices,
+            metadata,
+            outline_vertices,
+            outline_indices,
+        } = self;
+
+        let outline_vertex_buffer = device.create_buffer_init(&{
+            wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&outline_vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            }
+        });
+
+        let outline_index_buffer = device.create_buffer_init(&{
+            wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&outline_indices),
+                usage: wgpu::BufferUsages::INDEX,
+            }
+        });
+
+        let outline = Geometry {
+            vertices: outline_vertices,
+            vertex_buffer: outline_vertex_buffer,
+            indices: outline_indices,
+            index_buffer: outline_index_buffer,
+            metadata: (),
+        };
+
+        let vertex_buffer = device.create_buffer_init(&{
+            wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            }
+        });
+    
+        let index_buffer = device.create_buffer_init(&{
+            wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&indices),
+                usage: wgpu::BufferUsages::INDEX,
+            }
+        });
+    
+        let geometry = Geometry {
+            vertices,
+            vertex_buffer,
+            indices,
+            index_buffer,
+            metadata,
+        };
+
+        (geometry, outline)
+    }
+}
+
+// triangulates every level of detail of a feature set, finest first
+// the work is done a few features at a time so it can be spread out
+// where it can't be moved to another thread
+pub struct FeatureMeshBuilder {
+    // geometries of the features that passed validation
+    features: Vec<geojson::Value>,
+    // their properties, parallel to features
+    entries: sync::Arc<Vec<geojson::JsonObject>>,
+    shared: collections::HashMap<[i32; 2], SharedPoint>,
+    maxima: f32,
+    globe_radius: f32,
+    // the level being built
+    geometry: TempFeatureGeometry,
+    // level of detail and feature to continue from
+    cursor: [usize; 2],
+    // every level is colored like the finest one so colors don't shift while zooming
+    colors: Option<Vec<[u8; 3]>>,
+    meshes: Vec<FeatureMesh>,
+}
+
+impl FeatureMeshBuilder {
+    pub fn new(
+        features: Vec<geojson::Feature>,
         slices: u32,
         stacks: u32,
         globe_radius: f32,
-    ) -> Result<Self, earcutr::Error> {
-        use wgpu::util::DeviceExt as _;
-
+        tolerance: f32,
+    ) -> Self {
         fn validation(feature: &geojson::Feature) -> Option<TempFeature<'_>> {
             use geojson::JsonValue;
 
@@ -224,17 +442,126 @@ impl Geometry<FeatureVertex, FeatureMetadata> {
             a.min(b)
         };
 
-        let mut geometry = TempFeatureGeometry::default();
+        // the longest chord whose midpoint dips no further than `tolerance` below the surface
+        let edge_maxima = (1. - tolerance / globe_radius).clamp(-1., 1.).acos() * 2.;
+
+        let (features, entries): (Vec<_>, Vec<_>) = features
+            .into_iter()
+            .filter(|feature| validation(feature).is_some())
+            .filter_map(|geojson::Feature { geometry, properties, .. }| {
+                Some((geometry?.value, properties?))
+            }).unzip();
 
-        for feature in features.iter().filter_map(validation) {
-            geometry.add_feature(feature, maxima, globe_radius)?;
+        let shared = shared_points(features.iter());
+
+        Self {
+            features,
+            entries: sync::Arc::new(entries),
+            shared,
+            maxima,
+            globe_radius,
+            geometry: TempFeatureGeometry { edge_maxima, ..Default::default() },
+            cursor: [0; 2],
+            colors: None,
+            meshes: Vec::with_capacity(LOD_TOLERANCES.len()),
         }
+    }
 
-        let TempFeatureGeometry { 
-            vertices, 
-            indices,
-            feature_metadata: metadata,
-        } = geometry;
+    // triangulates up to `count` more features of the current level
+    // returns true once every level has been built
+    pub fn step(&mut self, count: usize, style: &style::Style) -> Result<bool, earcutr::Error> {
+        use std::mem;
+
+        let Self {
+            features,
+            entries,
+            shared,
+            maxima,
+            globe_radius,
+            geometry,
+            cursor: [level, next],
+            colors,
+            meshes,
+        } = self;
+
+        let Some(lod_tolerance) = LOD_TOLERANCES.get(*level).copied() else { return Ok(true); };
+
+        let end = next.saturating_add(count).min(features.len());
+
+        for (value, metadata) in features[*next..end].iter().zip(&entries[*next..end]) {
+            let simplified = (lod_tolerance > 0.)
+                .then(|| simplify(value, shared, lod_tolerance));
+
+            let value = simplified.as_ref().unwrap_or(value);
+
+            geometry.add_feature(TempFeature { value, metadata }, *maxima, *globe_radius, style)?;
+        }
+
+        *next = end;
+
+        if end < features.len() { return Ok(false); }
+
+        let temp = TempFeatureGeometry { edge_maxima: geometry.edge_maxima, ..Default::default() };
+
+        let geometry = mem::replace(geometry, temp);
+
+        let colors = colors.get_or_insert_with(|| {
+            let mut colors = geometry.feature_metadata.colors.clone();
+
+            style::separate_adjacent(&mut colors, &geometry.fill_keys, &geometry.adjacency());
+
+            colors
+        });
+
+        meshes.push(geometry.into_mesh(entries, colors));
+
+        *level += 1;
+        *next = 0;
+
+        Ok(*level >= LOD_TOLERANCES.len())
+    }
+
+    // builds whatever is left in one go
+    // the web build steps through it instead, see loader::FeatureManager::spawn
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn build(mut self, style: &style::Style) -> Result<Vec<FeatureMesh>, earcutr::Error> {
+        while !self.step(usize::MAX, style)? { /*  */ }
+
+        Ok(self.finish())
+    }
+
+    // the levels built so far, finest first
+    pub fn finish(self) -> Vec<FeatureMesh> {
+        self.meshes
+    }
+}
+
+impl Geometry<MarkerVertex, ()> {
+    // takes the position, color and date range of each marker
+    pub fn build_marker_geometry(
+        device: &wgpu::Device,
+        markers: &[([f32; 3], [u8; 3], [i32; 2])],
+    ) -> Self {
+        use wgpu::util::DeviceExt as _;
+
+        let mut vertices = Vec::with_capacity(markers.len() * 4);
+        let mut indices = Vec::with_capacity(markers.len() * 6);
+
+        for (idx, (center, [r, g, b], years)) in markers.iter().copied().enumerate() {
+            let vertices_len = vertices.len() as u32;
+
+            vertices.extend([[-1., -1.], [1., -1.], [-1., 1.], [1., 1.]].map(|corner| {
+                MarkerVertex {
+                    center,
+                    corner,
+                    color: [r as f32 / 255., g as f32 / 255., b as f32 / 255.],
+                    marker: idx as u32,
+                    years,
+                }
+            }));
+
+            indices.extend([0, 1, 2, 2, 1, 3].map(|temp| temp + vertices_len));
+        }
 
         let vertex_buffer = device.create_buffer_init(&{
             wgpu::util::BufferInitDescriptor {
@@ -251,19 +578,112 @@ impl Geometry<FeatureVertex, FeatureMetadata> {
                 usage: wgpu::BufferUsages::INDEX,
             }
         });
+
+        Geometry {
+            vertices,
+            vertex_buffer,
+            indices,
+            index_buffer,
+            metadata: (),
+        }
+    }
+}
+
+impl Geometry<RouteVertex, ()> {
+    // height of a route above the surface, as a fraction of the radius
+    const ROUTE_ALTITUDE: f32 = 0.002;
+
+    // routes arch away from the globe by this fraction of the radius per radian travelled
+    const ROUTE_ARCH: f32 = 0.02;
+
+    // takes the waypoints and color of each route, waypoints are given as [lat, lon]
+    // consecutive waypoints are joined along great circles
+    pub fn build_route_geometry(
+        device: &wgpu::Device,
+        routes: &[(Vec<[f32; 2]>, [u8; 3])],
+        maxima: f32,
+        globe_radius: f32,
+    ) -> Self {
+        use core::f32;
+
+        use wgpu::util::DeviceExt as _;
+
+        let mut vertices = Vec::with_capacity(0);
+        let mut indices = Vec::with_capacity(0);
+
+        for (waypoints, [r, g, b]) in routes.iter() {
+            let color = [*r as f32 / 255., *g as f32 / 255., *b as f32 / 255.];
+
+            let path = great_circle_path(waypoints.iter().copied(), maxima);
+
+            let angles = path
+                .windows(2)
+                .map(|edge| edge[0].dot(edge[1]).clamp(-1., 1.).acos())
+                .collect::<Vec<_>>();
+
+            let total = angles.iter().sum::<f32>();
+
+            if total <= 0. { continue; }
+
+            // lifts a point travelled `angle` radians along the route
+            let lift = |pt: ultraviolet::Vec3, angle: f32| {
+                let arch = (f32::consts::PI * angle / total).sin() * total * Self::ROUTE_ARCH;
+
+                *(pt * globe_radius * (1. + Self::ROUTE_ALTITUDE + arch)).as_array()
+            };
+
+            let mut angle = 0.;
+
+            for (edge, step) in path.windows(2).zip(angles) {
+                let start = lift(edge[0], angle);
+                let end = lift(edge[1], angle + step);
+
+                let vertices_len = vertices.len() as u32;
+
+                vertices.extend([[0., -1.], [0., 1.], [1., -1.], [1., 1.]].map(|corner| {
+                    RouteVertex {
+                        start,
+                        end,
+                        corner,
+                        distance: (angle + step * corner[0]).to_degrees(),
+                        color,
+                    }
+                }));
+
+                indices.extend([0, 1, 2, 2, 1, 3].map(|temp| temp + vertices_len));
+
+                angle += step;
+            }
+        }
+
+        let vertex_buffer = device.create_buffer_init(&{
+            wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            }
+        });
     
-        Ok(Geometry {
+        let index_buffer = device.create_buffer_init(&{
+            wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&indices),
+                usage: wgpu::BufferUsages::INDEX,
+            }
+        });
+
+        Geometry {
             vertices,
             vertex_buffer,
             indices,
             index_buffer,
-            metadata,
-        })
+            metadata: (),
+        }
     }
 }
 
 struct TempFeature<'a> {
-    geometry: &'a geojson::Geometry,
+    value: &'a geojson::Value,
     metadata: &'a geojson::JsonObject,
 }
 
@@ -275,11 +695,11 @@ impl<'a> TempFeature<'a> {
         let geojson::Feature { geometry, properties, .. } = feature;
         
         match geometry {
-            Some(geometry) => {
+            Some(geojson::Geometry { value, .. }) => {
                 let metadata = properties.as_ref();
                 match metadata.map(|m| (predicate(m), m)) {
                     Some((passed, metadata)) if passed => //
-                        Some(Self { geometry, metadata }),
+                        Some(Self { value, metadata }),
                     _ => None,
                 }
             },
@@ -289,203 +709,968 @@ impl<'a> TempFeature<'a> {
     }
 }
 
-fn validate_triangle(
-    data: &mut Vec<f64>, 
-    tri: &[usize],
-    maxima: f32,
-) -> Vec<usize> {
-    struct MajorAxis {
-        d: ultraviolet::Vec2,
-        fst: [usize; 3],
-        snd: [usize; 3],
+// how a border point is shared between rings, see simplify_ring
+#[derive(Default)]
+struct SharedPoint {
+    // polygons touching the point, numbered across the whole feature set
+    polygons: Vec<usize>,
+    // distinct points next to it along the rings it's on
+    neighbours: Vec<[i32; 2]>,
+}
+
+fn shared_points<'a>(
+    values: impl Iterator<Item = &'a geojson::Value>,
+) -> collections::HashMap<[i32; 2], SharedPoint> {
+    fn visit(
+        value: &geojson::Value,
+        shared: &mut collections::HashMap<[i32; 2], SharedPoint>,
+        count: &mut usize,
+    ) {
+        use geojson::Value;
+
+        let mut add_polygon = |polygon: &geojson::PolygonType| {
+            for ring in polygon {
+                let keys = ring
+                    .iter()
+                    .map(TempFeatureGeometry::border_key)
+                    .collect::<Vec<_>>();
+
+                let len = ring_len(ring);
+
+                for idx in 0..len {
+                    let SharedPoint { polygons, neighbours } = shared.entry(keys[idx]).or_default();
+
+                    if polygons.last() != Some(count) { polygons.push(*count); }
+
+                    for neighbour in [keys[(idx + len - 1) % len], keys[(idx + 1) % len]] {
+                        if !neighbours.contains(&neighbour) { neighbours.push(neighbour); }
+                    }
+                }
+            }
+
+            *count += 1;
+        };
+
+        match value {
+            Value::Polygon(polygon) => add_polygon(polygon),
+            Value::MultiPolygon(multi_polygon) => multi_polygon.iter().for_each(add_polygon),
+            Value::GeometryCollection(geometries) => for geojson::Geometry { value, .. } in geometries {
+                visit(value, shared, count);
+            },
+            _ => { /*  */ },
+        }
     }
 
-    let [a_idx, b_idx, c_idx] = *tri else { unreachable!(); };
+    let mut shared = collections::HashMap::new();
+    let mut count = 0;
 
-    let a = ultraviolet::Vec2 {
-        x: data[a_idx * 2] as f32, 
-        y: data[a_idx * 2 + 1] as f32 
-    };
+    for value in values {
+        visit(value, &mut shared, &mut count);
+    }
 
-    let b = ultraviolet::Vec2 {
-        x: data[b_idx * 2] as f32, 
-        y: data[b_idx * 2 + 1] as f32
-    };
+    shared
+}
 
-    let c = ultraviolet::Vec2 {
-        x: data[c_idx * 2] as f32, 
-        y: data[c_idx * 2 + 1] as f32
-    };
+// number of distinct points in a ring, the closing point repeats the first
+fn ring_len(ring: &[geojson::Position]) -> usize {
+    if ring.len() > 1 && ring.first() == ring.last() { ring.len() - 1 } else { ring.len() }
+}
 
-    let ab = b - a;
-    let bc = c - b;
-    let ca = a - c;
+// simplifies the polygons and lines of a geometry, dropping shapes that collapse
+// borders shared between polygons are simplified the same way on both sides
+fn simplify(
+    value: &geojson::Value,
+    shared: &collections::HashMap<[i32; 2], SharedPoint>,
+    tolerance: f64,
+) -> geojson::Value {
+    use geojson::Value;
 
-    let ab_mag = ab.mag().abs();
-    let bc_mag = bc.mag().abs();
-    let ca_mag = ca.mag().abs();
+    // holes are dropped along with the exterior ring
+    let polygon = |polygon: &geojson::PolygonType| -> Option<geojson::PolygonType> {
+        let (exterior, holes) = polygon.split_first()?;
 
-    let d_idx = data.len() / 2;
+        let exterior = simplify_ring(exterior, shared, tolerance)?;
 
-    let axis = if ab_mag > bc_mag && ab_mag > maxima {
-        if ab_mag > ca_mag {
-            Some(MajorAxis {
-                d: a + ab * 0.5,
-                fst: [a_idx, d_idx, c_idx],
-                snd: [b_idx, c_idx, d_idx],
-            })
-        } else {
-            Some(MajorAxis {
-                d: c + ca * 0.5,
-                fst: [a_idx, b_idx, d_idx],
-                snd: [b_idx, c_idx, d_idx],
-            })
-        }
-    } else if bc_mag > ca_mag && bc_mag > maxima {
-        Some(MajorAxis {
-            d:  b + bc * 0.5,
-            fst: [a_idx, b_idx, d_idx],
-            snd: [a_idx, d_idx, c_idx],
-        })
-    } else if ca_mag > maxima {
-        Some(MajorAxis {
-            d: c + ca * 0.5,
-            fst: [a_idx, b_idx, d_idx],
-            snd: [b_idx, c_idx, d_idx],
-        })
-    } else {
-        None
+        Some(std::iter::once(exterior)
+            .chain(holes.iter().filter_map(|ring| simplify_ring(ring, shared, tolerance)))
+            .collect())
     };
 
-    match axis {
-        Some(MajorAxis { d, fst, snd }) => {
-            data.push(d.x as f64);
-            data.push(d.y as f64);
+    let line = |line: &geojson::LineStringType| -> geojson::LineStringType {
+        let path = line.iter().map(|pt| [pt[0], pt[1]]).collect::<Vec<_>>();
 
-            let mut indices = Vec::with_capacity(6);
-            indices.append(&mut validate_triangle(data, &fst, maxima));
-            indices.append(&mut validate_triangle(data, &snd, maxima));
+        douglas_peucker(&path, tolerance)
+            .into_iter()
+            .map(|idx| line[idx].clone())
+            .collect()
+    };
 
-            indices
+    match value {
+        Value::Polygon(temp) => match polygon(temp) {
+            Some(temp) => Value::Polygon(temp),
+            None => Value::MultiPolygon(Vec::with_capacity(0)),
         },
-        None => tri.to_vec(),
+        Value::MultiPolygon(multi_polygon) => Value::MultiPolygon(multi_polygon.iter().filter_map(polygon).collect()),
+        Value::LineString(temp) if temp.len() > 1 => Value::LineString(line(temp)),
+        Value::MultiLineString(multi_line) => Value::MultiLineString({
+            multi_line.iter().filter(|temp| temp.len() > 1).map(line).collect()
+        }),
+        Value::GeometryCollection(geometries) => Value::GeometryCollection({
+            geometries
+                .iter()
+                .map(|geojson::Geometry { value, .. }| geojson::Geometry::new(simplify(value, shared, tolerance)))
+                .collect()
+        }),
+        value => value.clone(),
     }
 }
 
-#[derive(Default)]
+// a ring is cut into arcs at junctions, where more than two borders meet
+// or where the polygons sharing its border change
+// each arc is simplified on its own, walked in the same direction from either side
+// so neighbours keep the same points along their common border and no gaps open between them
+fn simplify_ring(
+    ring: &[geojson::Position],
+    shared: &collections::HashMap<[i32; 2], SharedPoint>,
+    tolerance: f64,
+) -> Option<Vec<geojson::Position>> {
+    let len = ring_len(ring);
+
+    if len < 3 { return None; }
+
+    let keys = ring[..len]
+        .iter()
+        .map(TempFeatureGeometry::border_key)
+        .collect::<Vec<_>>();
+
+    let polygons = |idx: usize| shared
+        .get(&keys[idx % len])
+        .map(|SharedPoint { polygons, .. }| polygons);
+
+    let junction = |idx: usize| shared
+        .get(&keys[idx])
+        .is_some_and(|SharedPoint { neighbours, .. }| neighbours.len() > 2);
+
+    let mut nodes = (0..len)
+        .filter(|idx| {
+            junction(*idx) || //
+                polygons(*idx) != polygons(idx + len - 1) || //
+                polygons(*idx) != polygons(idx + 1)
+        }).collect::<Vec<_>>();
+
+    // rings sharing their whole border with the same polygons start at their lowest point
+    // which is the same point on every one of them
+    if nodes.is_empty() {
+        nodes.extend((0..len).min_by_key(|idx| keys[*idx]));
+    }
+
+    let mut simplified = Vec::with_capacity(len + 1);
+
+    for (pos, start) in nodes.iter().copied().enumerate() {
+        let end = nodes[(pos + 1) % nodes.len()];
+
+        // an arc that starts and ends on the same node goes all the way around
+        let steps = (end + len - start - 1) % len + 1;
+
+        let mut arc = (0..=steps)
+            .map(|step| (start + step) % len)
+            .collect::<Vec<_>>();
+
+        let reversed = (keys[arc[steps]], keys[arc[steps - 1]]) < (keys[arc[0]], keys[arc[1]]);
+
+        if reversed { arc.reverse(); }
+
+        let path = arc
+            .iter()
+            .map(|idx| [ring[*idx][0], ring[*idx][1]])
+            .collect::<Vec<_>>();
+
+        let mut kept = douglas_peucker(&path, tolerance)
+            .into_iter()
+            .map(|idx| arc[idx])
+            .collect::<Vec<_>>();
+
+        if reversed { kept.reverse(); }
+
+        // the end of an arc is the start of the next
+        kept.pop();
+
+        simplified.extend(kept.into_iter().map(|idx| ring[idx].clone()));
+    }
+
+    if simplified.len() < 3 { return None; }
+
+    simplified.push(simplified[0].clone());
+
+    Some(simplified)
+}
+
+// indices of the points of a path kept by Douglas-Peucker, in order
+// the first and last points are always kept
+fn douglas_peucker(path: &[[f64; 2]], tolerance: f64) -> Vec<usize> {
+    // distance from p to the segment between a and b
+    fn distance(p: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+        let [dx, dy] = [b[0] - a[0], b[1] - a[1]];
+
+        let len_sq = dx * dx + dy * dy;
+
+        let t = if len_sq > 0. {
+            (((p[0] - a[0]) * dx + (p[1] - a[1]) * dy) / len_sq).clamp(0., 1.)
+        } else { 0. };
+
+        (p[0] - a[0] - t * dx).hypot(p[1] - a[1] - t * dy)
+    }
+
+    let mut kept = vec![false; path.len()];
+
+    if let [first, .., last] = kept.as_mut_slice() {
+        *first = true;
+        *last = true;
+    }
+
+    let mut pending = vec![(0, path.len().saturating_sub(1))];
+
+    while let Some((first, last)) = pending.pop() {
+        let farthest = ((first + 1)..last)
+            .map(|idx| (idx, distance(path[idx], path[first], path[last])))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        if let Some((idx, _)) = farthest.filter(|(_, dist)| *dist > tolerance) {
+            kept[idx] = true;
+
+            pending.extend([(first, idx), (idx, last)]);
+        }
+    }
+
+    kept.iter()
+        .enumerate()
+        .filter(|(_, kept)| **kept)
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+// the pole a ring winds around, 1 for the north pole and -1 for the south pole
+// longitudes of such a ring add up to a full turn instead of cancelling out
+fn enclosed_pole(ring: &[geojson::Position]) -> Option<f64> {
+    let winding = ring
+        .windows(2)
+        .map(|edge| {
+            let delta = edge[1][0] - edge[0][0];
+
+            delta - (delta / 360.).round() * 360.
+        }).sum::<f64>();
+
+    if winding.abs() < 180. { return None; }
+
+    let lat = ring.iter().map(|pt| pt[1]).sum::<f64>();
+
+    Some(if lat < 0. { -1. } else { 1. })
+}
+
+// azimuthal equidistant projection centered on a pole, in degrees from the pole
+// the south pole is mirrored so triangles keep the same winding on both hemispheres
+fn to_polar(pt: &geojson::Position, pole: f64) -> Vec<f64> {
+    let r = 90. - pt[1] * pole;
+
+    let theta = pt[0].to_radians();
+
+    vec![r * theta.cos(), r * theta.sin() * pole]
+}
+
+// inverse of to_polar, returns [lat, lon]
+fn from_polar([x, y]: [f64; 2], pole: f64) -> [f32; 2] {
+    let r = x.hypot(y);
+
+    [((90. - r) * pole) as f32, (y * pole).atan2(x).to_degrees() as f32]
+}
+
+// rings crossing the antimeridian jump by about 360 degrees of longitude between two points
+// which earcut would bridge with triangles spanning the whole globe
+// longitudes are carried past ±180 instead so each ring is continuous
+// positions on the globe are unaffected since longitude wraps around
+fn unwrap_antimeridian(polygon: &geojson::PolygonType) -> Option<geojson::PolygonType> {
+    let crosses = polygon
+        .iter()
+        .any(|ring| ring.windows(2).any(|edge| (edge[1][0] - edge[0][0]).abs() > 180.));
+
+    if !crosses { return None; }
+
+    let mut unwrapped = Vec::with_capacity(polygon.len());
+
+    // mean longitude of the exterior ring
+    let mut reference: Option<f64> = None;
+
+    for ring in polygon {
+        let mut offset = 0.;
+        let mut prev = None;
+
+        let mut ring = ring
+            .iter()
+            .map(|pt| {
+                match prev.map(|prev| pt[0] - prev) {
+                    Some(delta) if delta > 180. => offset -= 360.,
+                    Some(delta) if delta < -180. => offset += 360.,
+                    _ => { /*  */ },
+                }
+
+                prev = Some(pt[0]);
+
+                let mut pt = pt.clone();
+                pt[0] += offset;
+                pt
+            }).collect::<Vec<_>>();
+
+        let mean = ring.iter().map(|pt| pt[0]).sum::<f64>() / (ring.len().max(1) as f64);
+
+        // holes are moved alongside the exterior ring
+        match reference {
+            Some(reference) => {
+                let shift = ((reference - mean) / 360.).round() * 360.;
+
+                for pt in ring.iter_mut() { pt[0] += shift; }
+            },
+            None => reference = Some(mean),
+        }
+
+        unwrapped.push(ring);
+    }
+
+    Some(unwrapped)
+}
+
+// splits fill triangles until no edge spans more than `maxima` radians
+// an edge is split at its midpoint on the sphere, which is shared with the triangle across it
+// so both triangles agree on the split and no cracks open between them
+// triangles stop being split after SUBDIVISION_DEPTH rounds regardless
+fn subdivide(
+    points: &mut Vec<ultraviolet::Vec3>,
+    triangles: &[usize],
+    maxima: f32,
+) -> Vec<usize> {
+    const SUBDIVISION_DEPTH: u32 = 12;
+
+    fn midpoint(
+        points: &mut Vec<ultraviolet::Vec3>,
+        midpoints: &mut collections::HashMap<[usize; 2], usize>,
+        a: usize,
+        b: usize,
+    ) -> usize {
+        *midpoints.entry([a.min(b), a.max(b)]).or_insert_with(|| {
+            points.push((points[a] + points[b]).normalized());
+            points.len() - 1
+        })
+    }
+
+    let maxima_cos = maxima.cos();
+
+    let mut midpoints = collections::HashMap::new();
+
+    let mut pending = triangles
+        .chunks_exact(3)
+        .map(|tri| ([tri[0], tri[1], tri[2]], 0))
+        .collect::<Vec<_>>();
+
+    let mut indices = Vec::with_capacity(triangles.len());
+
+    while let Some((tri, depth)) = pending.pop() {
+        let split = [0, 1, 2].map(|i| {
+            points[tri[i]].dot(points[tri[(i + 1) % 3]]) < maxima_cos
+        });
+
+        if depth >= SUBDIVISION_DEPTH || split == [false; 3] {
+            indices.extend(tri);
+
+            continue;
+        }
+
+        // rotate the corners so the long edges come first
+        // the winding is kept, so the pieces face the same way as the triangle
+        let k = (0..3)
+            .find(|k| split[*k] && !split[(k + 2) % 3])
+            .unwrap_or(0);
+
+        let [a, b, c] = [0, 1, 2].map(|i| tri[(k + i) % 3]);
+
+        let pieces = match [0, 1, 2].map(|i| split[(k + i) % 3]) {
+            [true, false, false] => {
+                let ab = midpoint(points, &mut midpoints, a, b);
+
+                vec![[a, ab, c], [ab, b, c]]
+            },
+            [true, true, false] => {
+                let ab = midpoint(points, &mut midpoints, a, b);
+                let bc = midpoint(points, &mut midpoints, b, c);
+
+                vec![[a, ab, bc], [ab, b, bc], [a, bc, c]]
+            },
+            _ => {
+                let ab = midpoint(points, &mut midpoints, a, b);
+                let bc = midpoint(points, &mut midpoints, b, c);
+                let ca = midpoint(points, &mut midpoints, c, a);
+
+                vec![[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]]
+            },
+        };
+
+        pending.extend(pieces.into_iter().map(|piece| (piece, depth + 1)));
+    }
+
+    indices
+}
+
+#[derive(Default)]
 struct TempFeatureGeometry {
     vertices: Vec<FeatureVertex>,
     indices: Vec<u32>,
     feature_metadata: FeatureMetadata,
+    outline_vertices: Vec<OutlineVertex>,
+    outline_indices: Vec<u32>,
+    // the property value that chose each feature's color
+    fill_keys: Vec<Option<String>>,
+    // features touching each border point, used to find neighbours
+    border_points: collections::HashMap<[i32; 2], Vec<usize>>,
+    // longest edge of a fill triangle in radians, see subdivide
+    edge_maxima: f32,
 }
 
 impl TempFeatureGeometry {
+    // border points closer than 1 / BORDER_PRECISION degrees are considered shared
+    const BORDER_PRECISION: f64 = 1e4;
+
+    // in degrees, across the whole ribbon
+    const RIBBON_WIDTH: f32 = 0.1;
+
+    // in degrees, from the center of a point to its rim
+    const MARKER_RADIUS: f32 = 0.15;
+    const MARKER_SEGMENTS: u32 = 12;
+
+    // indices of the features bordering each feature
+    fn adjacency(&self) -> Vec<Vec<usize>> {
+        let mut adjacency = vec![Vec::with_capacity(0); self.fill_keys.len()];
+
+        for neighbours in self.border_points.values() {
+            for a in neighbours.iter() {
+                for b in neighbours.iter().filter(|b| *b != a) {
+                    if !adjacency[*a].contains(b) { adjacency[*a].push(*b); }
+                }
+            }
+        }
+
+        adjacency
+    }
+
+    // border points are keyed on a grid so nearly identical points compare equal
+    fn border_key(pt: &geojson::Position) -> [i32; 2] {
+        // shared vertices are rarely bit-identical across features
+        [
+            (pt[0] * Self::BORDER_PRECISION).round() as i32, 
+            (pt[1] * Self::BORDER_PRECISION).round() as i32,
+        ]
+    }
+
+    // fills in vertex colors, leaving plain vectors behind
+    fn into_mesh(
+        self,
+        entries: &sync::Arc<Vec<geojson::JsonObject>>,
+        colors: &[[u8; 3]],
+    ) -> FeatureMesh {
+        let TempFeatureGeometry { 
+            mut vertices, 
+            indices,
+            feature_metadata: mut metadata,
+            outline_vertices,
+            outline_indices, ..
+        } = self;
+
+        metadata.entries = sync::Arc::clone(entries);
+        metadata.colors = colors.to_vec();
+
+        for vertex in vertices.iter_mut() {
+            let [r, g, b] = metadata.colors[vertex.feature as usize];
+
+            vertex.color = [r as f32 / 255., g as f32 / 255., b as f32 / 255.];
+        }
+
+        FeatureMesh {
+            vertices,
+            indices,
+            metadata,
+            outline_vertices,
+            outline_indices,
+        }
+    }
+
     fn add_feature(
         &mut self,
         feature: TempFeature<'_>,
         maxima: f32,
         globe_radius: f32,
+        style: &style::Style,
+    ) -> Result<(), earcutr::Error> {
+        let TempFeature { value, metadata } = feature;
+
+        let FeatureMetadata { colors, .. } = &mut self.feature_metadata;
+
+        // entries are filled in once per feature set, see FeatureMeshBuilder
+        let idx = colors.len();
+
+        let (color, fill_key) = style.fill(metadata);
+
+        colors.push(color);
+
+        self.fill_keys.push(fill_key.map(String::from));
+
+        let feature = [idx as u32, border_precision(metadata)];
+
+        self.add_value(value, feature, maxima, globe_radius + 1.)
+    }
+
+    // every shape of a geometry shares the feature's entry
+    fn add_value(
+        &mut self,
+        value: &geojson::Value,
+        feature: [u32; 2],
+        maxima: f32,
+        globe_radius: f32,
+    ) -> Result<(), earcutr::Error> {
+        use geojson::Value;
+
+        match value {
+            Value::Polygon(polygon) => {
+                self.add_polygon(polygon, feature, maxima, globe_radius)?;
+            },
+            Value::MultiPolygon(multi_polygon) => for polygon in multi_polygon {
+                self.add_polygon(polygon, feature, maxima, globe_radius)?;
+            },
+            Value::LineString(line) => {
+                self.add_line(line, feature, maxima, globe_radius);
+            },
+            Value::MultiLineString(multi_line) => for line in multi_line {
+                self.add_line(line, feature, maxima, globe_radius);
+            },
+            Value::Point(pt) => {
+                self.add_point(pt, feature, globe_radius);
+            },
+            Value::MultiPoint(multi_point) => for pt in multi_point {
+                self.add_point(pt, feature, globe_radius);
+            },
+            Value::GeometryCollection(geometries) => for geojson::Geometry { value, .. } in geometries {
+                self.add_value(value, feature, maxima, globe_radius)?;
+            },
+        }
+
+        Ok(())
+    }
+
+    fn add_polygon(
+        &mut self,
+        polygon: &geojson::PolygonType,
+        [feature, precision]: [u32; 2],
+        maxima: f32,
+        globe_radius: f32,
     ) -> Result<(), earcutr::Error> {
         use core::f32;
 
         let Self { 
             vertices, 
             indices,
+            outline_vertices,
+            outline_indices,
+            border_points,
+            edge_maxima, ..
+        } = self;
+
+        let idx = feature as usize;
+
+        // exterior ring and holes alike
+        for ring in polygon {
+            for pt in ring.iter() {
+                let neighbours = border_points.entry(Self::border_key(pt)).or_default();
+
+                if neighbours.last() != Some(&idx) { neighbours.push(idx); }
+            }
+
+            let ring = ring
+                .iter()
+                .map(|pt| [pt[1] as f32, pt[0] as f32]);
+
+            add_outline(
+                outline_vertices, 
+                outline_indices, 
+                ring, 
+                [feature, precision], 
+                maxima, 
+                globe_radius,
+            );
+        }
+
+        let mut bb_min = [f32::MAX; 2];
+        let mut bb_max = [f32::MIN; 2];
+
+        // polygons around a pole are triangulated in a projection centered on it
+        // others are triangulated in lon/lat, unwrapped if they cross the antimeridian
+        let pole = polygon.first().and_then(|ring| enclosed_pole(ring));
+
+        let (data, holes, dims) = match pole {
+            Some(pole) => earcutr::flatten(&{
+                polygon
+                    .iter()
+                    .map(|ring| ring.iter().map(|pt| to_polar(pt, pole)).collect())
+                    .collect::<Vec<Vec<Vec<f64>>>>()
+            }),
+            None => earcutr::flatten(unwrap_antimeridian(polygon).as_ref().unwrap_or(polygon)),
+        };
+
+        let to_lat_lon = |pt: [f64; 2]| match pole {
+            Some(pole) => from_polar(pt, pole),
+            None => [pt[1] as f32, pt[0] as f32],
+        };
+
+        let vertices_len = vertices.len();
+        let indices_len = indices.len();
+
+        let mut points = data
+            .chunks_exact(2)
+            .map(|pt| {
+                let pt = [pt[0], pt[1]];
+
+                // the bounding box is found in the plane the polygon was triangulated in
+                bb_min[0] = bb_min[0].min(pt[0] as f32);
+                bb_min[1] = bb_min[1].min(pt[1] as f32);
+
+                bb_max[0] = bb_max[0].max(pt[0] as f32);
+                bb_max[1] = bb_max[1].max(pt[1] as f32);
+
+                ultraviolet::Vec3::from(util::lat_lon_to_vertex(to_lat_lon(pt), 1.))
+            }).collect::<Vec<_>>();
+
+        let triangles = earcutr::earcut(&data, &holes, dims)?;
+
+        let polygon_indices: Vec<u32> = subdivide(&mut points, &triangles, *edge_maxima)
+            .into_iter()
+            .map(|idx| (idx + vertices_len) as u32)
+            .collect();
+
+        indices.extend_from_slice(&polygon_indices);
+
+        vertices.extend(points.into_iter().map(|pt| {
+            let pos = *(pt * globe_radius).as_array();
+
+            // colors are filled in once neighbours have been told apart
+            FeatureVertex { pos, color: [0.; 3], feature, precision }
+        }));
+
+        let mut centroid_sum = 0.;
+        let mut centroid_accum = ultraviolet::Vec3::zero();
+
+        for tri in polygon_indices.chunks_exact(3) {
+            let [a_idx, b_idx, c_idx] = *tri else { unreachable!(); };
+
+            if (holes.contains(&(a_idx as usize - vertices_len))) || //
+                holes.contains(&(b_idx as usize - vertices_len)) || //
+                holes.contains(&(c_idx as usize - vertices_len)) { continue; }
+
+            let a = ultraviolet::Vec3::from(vertices[a_idx as usize].pos);
+            let b = ultraviolet::Vec3::from(vertices[b_idx as usize].pos);
+            let c = ultraviolet::Vec3::from(vertices[c_idx as usize].pos);
+
+            let tri_centroid = (a + b + c) / 3.;
+
+            let tri_area = (b - a).cross(c - a).mag();       
+
+            centroid_sum += tri_area;
+            centroid_accum += tri_centroid * tri_area;         
+        }
+
+        let centroid = centroid_accum / centroid_sum;
+
+        let corners = [
+            [bb_min[0], bb_min[1]], 
+            [bb_min[0], bb_max[1]], 
+            [bb_max[0], bb_min[1]], 
+            [bb_max[0], bb_max[1]],
+        ].map(|[x, y]| to_lat_lon([x as f64, y as f64]));
+
+        self.add_bounds(idx, vertices_len, indices_len, corners, centroid, globe_radius);
+
+        Ok(())
+    }
+
+    // lines are drawn as ribbons of constant width that follow the globe
+    fn add_line(
+        &mut self,
+        line: &geojson::LineStringType,
+        [feature, precision]: [u32; 2],
+        maxima: f32,
+        globe_radius: f32,
+    ) {
+        let Self { vertices, indices, .. } = self;
+
+        let path = great_circle_path(line.iter().map(|pt| [pt[1] as f32, pt[0] as f32]), maxima);
+
+        if path.len() < 2 { return; }
+
+        let vertices_len = vertices.len();
+        let indices_len = indices.len();
+
+        let half_width = (Self::RIBBON_WIDTH * 0.5).to_radians().tan();
+
+        for (i, pt) in path.iter().enumerate() {
+            let prev = path[i.saturating_sub(1)];
+            let next = path[(i + 1).min(path.len() - 1)];
+
+            // offset perpendicular to the direction of travel
+            let side = pt.cross(next - prev).normalized() * half_width;
+
+            vertices.extend([*pt + side, *pt - side].map(|pos| FeatureVertex {
+                pos: *(pos.normalized() * globe_radius).as_array(),
+                color: [0.; 3],
+                feature,
+                precision,
+            }));
+        }
+
+        for i in 0..(path.len() - 1) {
+            let [a, b, c, d] = [0, 1, 2, 3].map(|temp| (vertices_len + i * 2 + temp) as u32);
+
+            indices.extend(wind(vertices, [a, b, c]));
+            indices.extend(wind(vertices, [c, b, d]));
+        }
+
+        let (bb_min, bb_max) = line.iter().fold(([f32::MAX; 2], [f32::MIN; 2]), |(min, max), pt| {
+            let [lat, lon] = [pt[1] as f32, pt[0] as f32];
+
+            ([min[0].min(lat), min[1].min(lon)], [max[0].max(lat), max[1].max(lon)])
+        });
+
+        let centroid = path[path.len() / 2] * globe_radius;
+
+        let corners = bounding_corners([bb_min, bb_max]);
+
+        self.add_bounds(feature as usize, vertices_len, indices_len, corners, centroid, globe_radius);
+    }
+
+    // points are drawn as small discs on the surface
+    fn add_point(
+        &mut self,
+        pt: &geojson::Position,
+        [feature, precision]: [u32; 2],
+        globe_radius: f32,
+    ) {
+        use core::f32;
+
+        let Self { vertices, indices, .. } = self;
+
+        let [Some(lon), Some(lat)] = [pt.first(), pt.get(1)] else { return; };
+
+        let [lat, lon] = [*lat as f32, *lon as f32];
+
+        let center = ultraviolet::Vec3::from(util::lat_lon_to_vertex([lat, lon], 1.));
+
+        // any axis that isn't parallel to the center spans the tangent plane
+        let axis = if center.y.abs() < 0.9 { ultraviolet::Vec3::unit_y() } else { ultraviolet::Vec3::unit_x() };
+
+        let u = center.cross(axis).normalized();
+        let v = center.cross(u);
+
+        let radius = Self::MARKER_RADIUS.to_radians().tan();
+
+        let vertices_len = vertices.len();
+        let indices_len = indices.len();
+
+        vertices.extend((0..=Self::MARKER_SEGMENTS).map(|i| {
+            // the first vertex is the center, the rest go around the rim
+            let pos = match i {
+                0 => center,
+                i => {
+                    let theta = f32::consts::PI * 2. * (i - 1) as f32 / Self::MARKER_SEGMENTS as f32;
+
+                    (center + (u * theta.cos() + v * theta.sin()) * radius).normalized()
+                },
+            };
+
+            FeatureVertex { pos: *(pos * globe_radius).as_array(), color: [0.; 3], feature, precision }
+        }));
+
+        for i in 0..Self::MARKER_SEGMENTS {
+            let [a, b, c] = [0, i + 1, (i + 1) % Self::MARKER_SEGMENTS + 1]
+                .map(|temp| vertices_len as u32 + temp);
+
+            indices.extend(wind(vertices, [a, b, c]));
+        }
+
+        let bb_min = [lat - Self::MARKER_RADIUS, lon - Self::MARKER_RADIUS];
+        let bb_max = [lat + Self::MARKER_RADIUS, lon + Self::MARKER_RADIUS];
+
+        let corners = bounding_corners([bb_min, bb_max]);
+
+        self.add_bounds(feature as usize, vertices_len, indices_len, corners, center * globe_radius, globe_radius);
+    }
+
+    // records the extent of the shape whose vertices and indices start at the given offsets
+    fn add_bounds(
+        &mut self,
+        idx: usize,
+        vertices_len: usize,
+        indices_len: usize,
+        corners: [[f32; 2]; 4],
+        centroid: ultraviolet::Vec3,
+        globe_radius: f32,
+    ) {
+        let Self {
+            vertices,
+            indices,
             feature_metadata: FeatureMetadata {
-                entries,
-                colors,
                 bounding_boxes,
-            },
+                index_ranges,
+                bounding_spheres, ..
+            }, ..
         } = self;
 
-        let TempFeature {
-            geometry: geojson::Geometry { value, .. },
-            metadata, 
-        } = feature;
+        index_ranges.push(((indices_len as u32)..(indices.len() as u32), idx));
 
-        let color_raw = util::hashable_to_rgb8(metadata);
+        let shape_vertices = &vertices[vertices_len..];
 
-        let color = [
-            color_raw[0] as f32 / 255.,
-            color_raw[1] as f32 / 255.,
-            color_raw[2] as f32 / 255.,
-        ];
+        let center = shape_vertices
+            .iter()
+            .fold(ultraviolet::Vec3::zero(), |sum, v| sum + ultraviolet::Vec3::from(v.pos)) / //
+            (shape_vertices.len().max(1) as f32);
 
-        let globe_radius = globe_radius + 1.;
+        let radius = shape_vertices
+            .iter()
+            .fold(0f32, |r, v| r.max((ultraviolet::Vec3::from(v.pos) - center).mag()));
 
-        if let geojson::Value::MultiPolygon(multi_polygon) = value {
-            let idx = entries.len();
-            
-            entries.push(metadata.clone());
-            colors.push(color_raw);
+        bounding_spheres.push((*center.as_array(), radius));
 
-            for polygon in multi_polygon {
-                let mut bb_min = [f32::MAX; 2];
-                let mut bb_max = [f32::MIN; 2];
+        let [tl, tr, bl, br] = corners.map(|pt| util::lat_lon_to_vertex(pt, globe_radius));
 
-                let (mut data, holes, dims) = earcutr::flatten(polygon);
+        let bb = BoundingBox {
+            centroid: *centroid.as_array(), tl, tr, bl, br,
+        };
+
+        bounding_boxes.push((bb, idx));
+    }
+}
 
-                let vertices_len = vertices.len();
+// top left, top right, bottom left and bottom right corners of a box given in [lat, lon]
+fn bounding_corners([bb_min, bb_max]: [[f32; 2]; 2]) -> [[f32; 2]; 4] {
+    [bb_min, [bb_max[0], bb_min[1]], [bb_min[0], bb_max[1]], bb_max]
+}
 
-                let polygon_indices: Vec<u32> = earcutr::earcut(&data, &holes, dims)?
-                    .chunks_exact(3)
-                    .flat_map(|tri| validate_triangle(&mut data, tri, maxima))
-                    .map(|idx| (idx + vertices_len) as u32)
-                    .collect();
+// orders a triangle's corners the way earcut orders the triangles of a fill
+// so generated shapes survive the same face culling
+fn wind(vertices: &[FeatureVertex], [a, b, c]: [u32; 3]) -> [u32; 3] {
+    let [pa, pb, pc] = [a, b, c].map(|idx| ultraviolet::Vec3::from(vertices[idx as usize].pos));
 
-                indices.extend_from_slice(&polygon_indices);
+    if (pb - pa).cross(pc - pa).dot(pa) > 0. { [a, c, b] } else { [a, b, c] }
+}
 
-                vertices.extend(data.chunks_exact(2).map(|pt| {
-                    let pt = [pt[1] as f32, pt[0] as f32];
+// unit vectors along the great circle arcs joining each point to the next
+// arcs are split into steps no longer than `maxima` degrees
+fn great_circle_path(
+    path: impl Iterator<Item = [f32; 2]>,
+    maxima: f32,
+) -> Vec<ultraviolet::Vec3> {
+    // radians short of a half turn past which two points count as antipodal
+    const ANTIPODAL: f32 = 1e-3;
 
-                    bb_min[0] = bb_min[0].min(pt[0]);
-                    bb_min[1] = bb_min[1].min(pt[1]);
+    let mut points = Vec::with_capacity(0);
 
-                    bb_max[0] = bb_max[0].max(pt[0]);
-                    bb_max[1] = bb_max[1].max(pt[1]);
+    let mut prev_pt: Option<[f32; 2]> = None;
 
-                    let pos = util::lat_lon_to_vertex(pt, globe_radius);
+    for pt in path {
+        let Some(prev_pt) = prev_pt.replace(pt) else {
+            points.push(ultraviolet::Vec3::from(util::lat_lon_to_vertex(pt, 1.)));
 
-                    FeatureVertex { pos, color }
-                }));
+            continue;
+        };
 
-                let mut centroid_sum = 0.;
-                let mut centroid_accum = ultraviolet::Vec3::zero();
+        let prev: ultraviolet::Vec3 = points[points.len() - 1];
+        let next = ultraviolet::Vec3::from(util::lat_lon_to_vertex(pt, 1.));
 
-                for tri in polygon_indices.chunks_exact(3) {
-                    let [a_idx, b_idx, c_idx] = *tri else { unreachable!(); };
+        let angle = prev.dot(next).clamp(-1., 1.).acos();
 
-                    if (holes.contains(&(a_idx as usize - vertices_len))) || //
-                        holes.contains(&(b_idx as usize - vertices_len)) || //
-                        holes.contains(&(c_idx as usize - vertices_len)) { continue; }
+        // repeated points would leave no direction to travel in
+        if angle <= f32::EPSILON { continue; }
 
-                    let a = ultraviolet::Vec3::from(vertices[a_idx as usize].pos);
-                    let b = ultraviolet::Vec3::from(vertices[b_idx as usize].pos);
-                    let c = ultraviolet::Vec3::from(vertices[c_idx as usize].pos);
+        // no single great circle joins antipodal points, so the leg turns at their midpoint in lat/lon
+        // it lies a quarter turn from both of them
+        let legs = if std::f32::consts::PI - angle < ANTIPODAL {
+            let mid = [(prev_pt[0] + pt[0]) * 0.5, (prev_pt[1] + pt[1]) * 0.5];
+            let mid = ultraviolet::Vec3::from(util::lat_lon_to_vertex(mid, 1.));
 
-                    let tri_centroid = (a + b + c) / 3.;
+            vec![
+                (prev, mid, prev.dot(mid).clamp(-1., 1.).acos()),
+                (mid, next, mid.dot(next).clamp(-1., 1.).acos()),
+            ]
+        } else {
+            vec![(prev, next, angle)]
+        };
 
-                    let tri_area = (b - a).cross(c - a).mag();       
+        for (a, b, angle) in legs {
+            let steps = (angle.to_degrees() / maxima).ceil().max(1.) as u32;
 
-                    centroid_sum += tri_area;
-                    centroid_accum += tri_centroid * tri_area;         
-                }
+            for step in 1..=steps {
+                points.push(util::slerp(a, b, angle, step as f32 / steps as f32));
+            }
+        }
+    }
+
+    points
+}
 
-                let tl = util::lat_lon_to_vertex(bb_min, globe_radius);
-                let tr = util::lat_lon_to_vertex([bb_max[0], bb_min[1]], globe_radius);
-                let bl = util::lat_lon_to_vertex([bb_min[0], bb_max[1]], globe_radius);
-                let br = util::lat_lon_to_vertex(bb_max, globe_radius);
+// splits each edge of a ring into segments no longer than `maxima` degrees
+// so the outline follows the curvature of the globe
+fn add_outline(
+    vertices: &mut Vec<OutlineVertex>,
+    indices: &mut Vec<u32>,
+    ring: impl Iterator<Item = [f32; 2]>,
+    [feature, precision]: [u32; 2],
+    maxima: f32,
+    globe_radius: f32,
+) {
+    let ring = ring
+        .map(|pt| ultraviolet::Vec3::from(util::lat_lon_to_vertex(pt, 1.)))
+        .collect::<Vec<_>>();
 
-                let centroid = centroid_accum / centroid_sum;
+    let mut distance = 0.;
 
-                let bb = BoundingBox {
-                    centroid: *centroid.as_array(), tl, tr, bl, br,
-                };
+    for edge in ring.windows(2) {
+        let [a, b] = *edge else { unreachable!(); };
 
-                bounding_boxes.push((bb, idx));
-            }  
-        }
+        let angle = a.dot(b).clamp(-1., 1.).acos();
 
-        Ok(())
+        let steps = (angle.to_degrees() / maxima).ceil().max(1.) as u32;
+
+        let step_distance = angle.to_degrees() / steps as f32;
+
+        let mut prev = a;
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+
+            let next = util::slerp(a, b, angle, t);
+
+            let start = *(prev * globe_radius).as_array();
+            let end = *(next * globe_radius).as_array();
+
+            let vertices_len = vertices.len() as u32;
+
+            vertices.extend([[0., -1.], [0., 1.], [1., -1.], [1., 1.]].map(|corner| {
+                OutlineVertex { 
+                    start, 
+                    end, 
+                    corner, 
+                    distance: distance + step_distance * corner[0], 
+                    feature, 
+                    precision,
+                }
+            }));
+
+            indices.extend([0, 1, 2, 2, 1, 3].map(|temp| temp + vertices_len));
+
+            distance += step_distance;
+
+            prev = next;
+        }
     }
-}
\ No newline at end of file
+}
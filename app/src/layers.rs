@@ -0,0 +1,508 @@
+use backend::wgpu as wgpu;
+
+use super::{camera, geom, util, Border};
+
+use std::ops;
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Debug)]
+pub struct LayerUniform {
+    pub border_color: [f32; 4],
+    pub opacity: f32,
+    // brightens the fill when set to 1
+    pub highlight: f32,
+    // index of the feature under the cursor, u32::MAX if there is none
+    pub hovered: u32,
+    // in physical pixels
+    pub border_width: f32,
+}
+
+pub struct Layer {
+    // index into the feature catalog
+    pub idx: usize,
+    // finest first, see geom::lod_level
+    levels: Vec<geom::FeatureLevel>,
+    // the base layer follows the timeline, the rest are pinned overlays
+    pub base: bool,
+    pub visible: bool,
+    opacity: f32,
+    border: Border,
+    uniform_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl Layer {
+    // the finest level, which feature metadata goes through
+    pub fn geometry(&self) -> &geom::Geometry<geom::FeatureVertex, geom::FeatureMetadata> {
+        &self.levels[0].0
+    }
+
+    // falls back to the coarsest level built
+    pub fn level(&self, level: usize) -> &geom::FeatureLevel {
+        &self.levels[level.min(self.levels.len() - 1)]
+    }
+
+    fn write_uniform(&self, queue: &wgpu::Queue, hovered: Option<Pick>) {
+        let hovered = match hovered {
+            Some(Pick { layer, entry }) if layer == self.idx => entry as u32,
+            _ => u32::MAX,
+        };
+
+        let Border { width, color } = self.border;
+
+        let uniform = LayerUniform { 
+            border_color: color,
+            opacity: self.opacity, 
+            highlight: 0., 
+            hovered,
+            border_width: width,
+        };
+
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    fn destroy(self) {
+        let Self { levels, uniform_buffer, .. } = self;
+
+        Self::destroy_levels(levels);
+
+        uniform_buffer.destroy();
+    }
+
+    fn destroy_levels(levels: Vec<geom::FeatureLevel>) {
+        for (geometry, outline) in levels {
+            geometry.destroy();
+
+            outline.destroy();
+        }
+    }
+}
+
+// a feature hit by a ray cast into the scene
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug)]
+pub struct Pick {
+    // index into the feature catalog
+    pub layer: usize,
+    // index into geom::FeatureMetadata::entries
+    pub entry: usize,
+}
+
+// the base layer follows the timeline, overlays are pinned alongside it
+// layers start out with the base at the bottom and overlays in the order they were pinned
+// a selected layer can then be hidden, faded and moved up or down
+pub struct LayerStack {
+    // in draw order, bottom to top
+    layers: Vec<Layer>,
+    // index into layers
+    selected: Option<usize>,
+    bind_group_layout: wgpu::BindGroupLayout,
+    #[allow(dead_code)]
+    highlight_buffer: wgpu::Buffer,
+    highlight_bind_group: wgpu::BindGroup,
+    hovered: Option<Pick>,
+    base_opacity: f32,
+    overlay_opacity: f32,
+    base_border: Border,
+    overlay_border: Border,
+}
+
+impl LayerStack {
+    const OPACITY_STEP: f32 = 0.1;
+
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        base_opacity: f32,
+        overlay_opacity: f32,
+        base_border: Border,
+        overlay_border: Border,
+    ) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&{
+            wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    // outlines are widened in the vertex stage
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            }
+        });
+
+        let highlight_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: std::mem::size_of::<LayerUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let highlight_bind_group = device.create_bind_group(&{
+            wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: highlight_buffer.as_entire_binding(),
+                }],
+            }
+        });
+
+        queue.write_buffer(&highlight_buffer, 0, bytemuck::cast_slice(&[{
+            LayerUniform { 
+                border_color: [0.; 4], 
+                opacity: 1., 
+                highlight: 1., 
+                hovered: u32::MAX, 
+                border_width: 0.,
+            }
+        }]));
+
+        Self {
+            layers: Vec::with_capacity(0),
+            selected: None,
+            bind_group_layout,
+            highlight_buffer,
+            highlight_bind_group,
+            hovered: None,
+            base_opacity,
+            overlay_opacity,
+            base_border,
+            overlay_border,
+        }
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    fn build_layer(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        idx: usize,
+        levels: Vec<geom::FeatureLevel>,
+        base: bool,
+    ) -> Layer {
+        use std::mem;
+
+        let (opacity, border) = if base {
+            (self.base_opacity, self.base_border)
+        } else {
+            (self.overlay_opacity, self.overlay_border)
+        };
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: mem::size_of::<LayerUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&{
+            wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &self.bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                }],
+            }
+        });
+
+        let layer = Layer {
+            idx,
+            levels,
+            base,
+            visible: true,
+            opacity,
+            border,
+            uniform_buffer,
+            bind_group,
+        };
+
+        layer.write_uniform(queue, self.hovered);
+        layer
+    }
+
+    // a replaced layer keeps its place in the draw order, its visibility and its opacity
+    fn place(&mut self, queue: &wgpu::Queue, mut layer: Layer, pos: Option<usize>) {
+        match pos {
+            Some(pos) => {
+                let Layer { visible, opacity, .. } = self.layers[pos];
+
+                layer.visible = visible;
+                layer.opacity = opacity;
+
+                layer.write_uniform(queue, self.hovered);
+
+                std::mem::replace(&mut self.layers[pos], layer).destroy();
+            },
+            // the base layer goes beneath the overlays
+            None if layer.base => {
+                self.layers.insert(0, layer);
+
+                if let Some(selected) = self.selected.as_mut() { *selected += 1; }
+            },
+            None => self.layers.push(layer),
+        }
+    }
+
+    pub fn replace_base(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        idx: usize,
+        levels: Vec<geom::FeatureLevel>,
+    ) {
+        // a feature set is never shown twice, the base takes over from its overlay
+        self.remove_overlay(idx);
+
+        let layer = self.build_layer(device, queue, idx, levels, true);
+
+        let pos = self.layers.iter().position(|layer| layer.base);
+
+        self.place(queue, layer, pos);
+    }
+
+    pub fn insert_overlay(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        idx: usize,
+        levels: Vec<geom::FeatureLevel>,
+    ) {
+        // the base already shows this feature set
+        if self.base().is_some_and(|layer| layer.idx == idx) {
+            Layer::destroy_levels(levels);
+
+            return;
+        }
+
+        let layer = self.build_layer(device, queue, idx, levels, false);
+
+        let pos = self.overlay_position(idx);
+
+        self.place(queue, layer, pos);
+    }
+
+    fn overlay_position(&self, idx: usize) -> Option<usize> {
+        self.layers.iter().position(|layer| !layer.base && layer.idx == idx)
+    }
+
+    pub fn remove_overlay(&mut self, idx: usize) -> bool {
+        let Some(pos) = self.overlay_position(idx) else { return false; };
+
+        self.selected = match self.selected {
+            Some(selected) if selected == pos => None,
+            Some(selected) if selected > pos => Some(selected - 1),
+            selected => selected,
+        };
+
+        self.layers.remove(pos).destroy();
+
+        true
+    }
+
+    pub fn overlay(&self, idx: usize) -> Option<&Layer> {
+        self.overlay_position(idx).map(|pos| &self.layers[pos])
+    }
+
+    pub fn base(&self) -> Option<&Layer> {
+        self.layers.iter().find(|layer| layer.base)
+    }
+
+    // selects the layer showing the given feature set, overlays first
+    // selecting it again clears the selection
+    pub fn select(&mut self, idx: usize) -> bool {
+        let pos = self.overlay_position(idx).or_else(|| {
+            self.layers.iter().position(|layer| layer.base && layer.idx == idx)
+        });
+
+        match pos {
+            Some(pos) => {
+                self.selected = (self.selected != Some(pos)).then_some(pos);
+
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&Layer> {
+        self.selected.map(|pos| &self.layers[pos])
+    }
+
+    // the selected layer, or the topmost one when nothing is selected
+    fn target(&self) -> Option<usize> {
+        self.selected.or_else(|| self.layers.len().checked_sub(1))
+    }
+
+    pub fn toggle_visibility(&mut self) -> bool {
+        match self.target() {
+            Some(pos) => {
+                let layer = &mut self.layers[pos];
+
+                layer.visible = !layer.visible;
+
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn step_opacity(&mut self, queue: &wgpu::Queue, increase: bool) -> bool {
+        match self.target() {
+            Some(pos) => {
+                let step = if increase { Self::OPACITY_STEP } else { -Self::OPACITY_STEP };
+
+                let layer = &mut self.layers[pos];
+
+                layer.opacity = (layer.opacity + step).clamp(0., 1.);
+                layer.write_uniform(queue, self.hovered);
+
+                true
+            }, None => false,
+        }
+    }
+
+    // swaps the selected layer with the one above or below it
+    pub fn move_selected(&mut self, up: bool) -> bool {
+        let Some(pos) = self.selected else { return false; };
+
+        let other = if up { pos + 1 } else { pos.wrapping_sub(1) };
+
+        if other >= self.layers.len() { return false; }
+
+        self.layers.swap(pos, other);
+
+        self.selected = Some(other);
+
+        true
+    }
+
+    // visible layers in draw order
+    pub fn iter(&self) -> impl Iterator<Item = &Layer> {
+        self.layers
+            .iter()
+            .filter(|layer| layer.visible)
+    }
+
+    pub fn entry(&self, pick: Pick) -> Option<&geojson::JsonObject> {
+        let Pick { layer: idx, entry } = pick;
+
+        self.iter()
+            .find(|layer| layer.idx == idx)
+            .and_then(|layer| layer.geometry().metadata.entries.get(entry))
+    }
+
+    // returns true if the hovered feature changed
+    pub fn set_hovered(&mut self, queue: &wgpu::Queue, hovered: Option<Pick>) -> bool {
+        if self.hovered == hovered { return false; }
+
+        self.hovered = hovered;
+
+        for layer in self.layers.iter() {
+            layer.write_uniform(queue, hovered);
+        }
+
+        true
+    }
+
+    // centroid and extent of the picked feature's largest polygon
+    pub fn frame(&self, pick: Pick) -> Option<([f32; 3], f32)> {
+        let Pick { layer: idx, entry } = pick;
+
+        let layer = self.iter().find(|layer| layer.idx == idx)?;
+
+        layer.geometry().metadata.bounding_boxes
+            .iter()
+            .filter(|(_, temp)| *temp == entry)
+            .map(|(geom::BoundingBox { centroid, tl, br, .. }, _)| {
+                let extent = (ultraviolet::Vec3::from(tl) - ultraviolet::Vec3::from(br)).mag();
+
+                (*centroid, extent)
+            }).max_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    pub fn highlight_bind_group(&self) -> &wgpu::BindGroup {
+        &self.highlight_bind_group
+    }
+
+    // the geometry holding a picked feature at the given level of detail
+    // along with the index ranges that make up the feature
+    pub fn highlighted(
+        &self,
+        pick: Pick,
+        level: usize,
+    ) -> Option<(&geom::Geometry<geom::FeatureVertex, geom::FeatureMetadata>, impl Iterator<Item = ops::Range<u32>> + '_)> {
+        let Pick { layer: idx, entry } = pick;
+
+        let (geometry, _) = self.iter().find(|layer| layer.idx == idx)?.level(level);
+
+        let ranges = geometry.metadata.index_ranges
+            .iter()
+            .filter(move |(_, temp)| *temp == entry)
+            .map(|(range, _)| range.clone());
+
+        Some((geometry, ranges))
+    }
+
+    // finds the feature closest to the eye along the given ray
+    // overlays win ties with the layers beneath them
+    // tests the triangles of the given level of detail, see geom::lod_level
+    pub fn pick(
+        &self,
+        camera_uniform: camera::CameraUniform,
+        ray: [f32; 3],
+        globe_radius: f32,
+        level: usize,
+    ) -> Option<Pick> {
+        use core::f32;
+
+        let camera::CameraUniform { eye, .. } = camera_uniform;
+
+        let maxima_sq = util::hemisphere_maxima_sq(eye, globe_radius);
+
+        let mut nearest = (f32::MAX, None);
+
+        for layer in self.iter() {
+            let geom::Geometry { 
+                vertices, 
+                indices, 
+                metadata: geom::FeatureMetadata { 
+                    index_ranges, 
+                    bounding_spheres, .. 
+                }, .. 
+            } = &layer.level(level).0;
+
+            for ((range, entry), (center, radius)) in index_ranges.iter().zip(bounding_spheres) {
+                if !util::intrs_sphere(eye, ray, *center, *radius) { continue; }
+
+                let range = (range.start as usize)..(range.end as usize);
+
+                for tri in indices[range].chunks_exact(3) {
+                    let a = vertices[tri[0] as usize].pos;
+                    let b = vertices[tri[1] as usize].pos;
+                    let c = vertices[tri[2] as usize].pos;
+
+                    // triangulation doesn't guarantee a consistent winding
+                    let dist = util::intrs(eye, ray, a, b, c, maxima_sq)
+                        .min(util::intrs(eye, ray, a, c, b, maxima_sq));
+
+                    if dist < f32::MAX && dist <= nearest.0 {
+                        nearest = (dist, Some(Pick { layer: layer.idx, entry: *entry }));
+                    }
+                }
+            }
+        }
+
+        nearest.1
+    }
+}
@@ -0,0 +1,313 @@
+use backend::wgpu as wgpu;
+
+use super::{camera, geom, layers, util};
+
+use std::collections;
+
+struct LegendEntry {
+    pick: layers::Pick,
+    text: String,
+    color: [u8; 3],
+}
+
+// lists the features on screen with their colors, largest first
+// toggled with `L`, clicking an entry focuses the feature
+pub struct Legend {
+    font_system: glyphon::FontSystem,
+    font_attrs: glyphon::Attrs<'static>,
+    swash_cache: glyphon::SwashCache,
+    atlas: glyphon::TextAtlas,
+    buffer: glyphon::Buffer,
+    renderer: glyphon::TextRenderer,
+    visible: bool,
+    entries: Vec<LegendEntry>,
+    // top left corner and size of the panel
+    bounds: [f32; 4],
+    choice: Option<layers::Pick>,
+    screen_resolution: backend::Size,
+    // eye, viewport and visible layers the entries were collected for
+    collected_for: Option<([f32; 4], [f32; 4], Vec<usize>)>,
+}
+
+impl Legend {
+    const METRICS: glyphon::Metrics = glyphon::Metrics::new(18., 22.);
+
+    const WIDTH: f32 = 280.;
+    const MARGIN: f32 = 16.;
+
+    const ENTRIES_MAX: usize = 12;
+
+    const SWATCH: &'static str = "\u{25A0} ";
+
+    const COLOR_BASIC: glyphon::Color = glyphon::Color::rgb(255, 255, 255);
+
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        surface_format: wgpu::TextureFormat,
+        font_bytes: std::sync::Arc<Vec<u8>>,
+        font_family: &'static str,
+    ) -> Self {
+        let mut font_system = glyphon::FontSystem::new_with_fonts({
+            use glyphon::fontdb::Source;
+
+            Some(Source::Binary(font_bytes))
+        });
+
+        let mut atlas = glyphon::TextAtlas::new(
+            device,
+            queue,
+            surface_format
+        );
+
+        let buffer = glyphon::Buffer::new(&mut font_system, Self::METRICS);
+
+        let renderer = glyphon::TextRenderer::new(
+            &mut atlas,
+            device,
+            wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            }, None
+        );
+
+        Self {
+            font_system,
+            font_attrs: glyphon::Attrs::new().family(glyphon::Family::Name(font_family)),
+            swash_cache: glyphon::SwashCache::new(),
+            atlas,
+            buffer,
+            renderer,
+            visible: false,
+            entries: Vec::with_capacity(0),
+            bounds: [0.; 4],
+            choice: None,
+            screen_resolution: backend::Size::default(),
+            collected_for: None,
+        }
+    }
+
+    // the entry clicked by the last event, if any
+    pub fn take_choice(&mut self) -> Option<layers::Pick> {
+        self.choice.take()
+    }
+
+    pub fn handle_event(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        event: backend::AppEvent,
+    ) -> bool {
+        use backend::event::{KeyCode, ElementState, MouseButton};
+
+        match event {
+            backend::AppEvent::Resized(size) => {
+                self.screen_resolution = size;
+
+                self.refresh(device, queue);
+
+                // other components need to process size changes
+                false
+            },
+            backend::AppEvent::Key {
+                code: KeyCode::KeyL,
+                state: ElementState::Released,
+            } => {
+                self.visible = !self.visible;
+
+                self.refresh(device, queue);
+
+                true
+            },
+            backend::AppEvent::Mouse {
+                button: MouseButton::Left,
+                state,
+                cursor,
+            } if self.visible && self.contains(cursor) => {
+                if let ElementState::Released = state {
+                    self.choice = self.entry_at(cursor).map(|LegendEntry { pick, .. }| *pick);
+                }
+
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn contains(&self, backend::Position { x, y }: backend::Position) -> bool {
+        let [left, top, width, height] = self.bounds;
+
+        x >= left && x <= left + width && y >= top && y <= top + height
+    }
+
+    fn entry_at(&self, backend::Position { y, .. }: backend::Position) -> Option<&LegendEntry> {
+        let temp = ((y - self.bounds[1]) / Self::METRICS.line_height).floor();
+
+        if temp < 0. { return None; }
+
+        // runs map to entries one to one since lines don't wrap
+        self.entries.get(temp as usize)
+    }
+
+    // collects the features whose centroids are on screen
+    // nothing is done unless the camera or the visible layers changed since the last time
+    pub fn update(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        stack: &layers::LayerStack,
+        camera_uniform: camera::CameraUniform,
+    ) {
+        if !self.visible { return; }
+
+        let camera::CameraUniform { eye, view, proj, viewport } = camera_uniform;
+
+        let key = (eye, viewport, stack.iter().map(|layer| layer.idx).collect());
+
+        if self.collected_for.as_ref() == Some(&key) { return; }
+
+        self.collected_for = Some(key);
+
+        let eye = ultraviolet::Vec3::new(eye[0], eye[1], eye[2]);
+
+        let mut visible: Vec<LegendEntry> = Vec::with_capacity(0);
+
+        // features made of several polygons are ranked by their total area
+        let mut areas: collections::HashMap<layers::Pick, f32> = collections::HashMap::new();
+
+        for layer in stack.iter() {
+            let geom::FeatureMetadata {
+                entries,
+                colors,
+                bounding_boxes, ..
+            } = &layer.geometry().metadata;
+
+            for (geom::BoundingBox { centroid, tl, br, .. }, idx) in bounding_boxes.iter() {
+                // skip polygons on the far side of the globe
+                let normal = ultraviolet::Vec3::from(centroid);
+                if normal.dot(eye - normal) <= 0. { continue; }
+
+                let [x, y] = util::world_to_screen_space(*centroid, view, proj);
+                if x.abs() > 1. || y.abs() > 1. { continue; }
+
+                let clip = |[x, y]: [f32; 2]| [x.clamp(-1., 1.), y.clamp(-1., 1.)];
+
+                let [x0, y0] = clip(util::world_to_screen_space(*tl, view, proj));
+                let [x1, y1] = clip(util::world_to_screen_space(*br, view, proj));
+
+                let area = ((x1 - x0) * (y1 - y0)).abs();
+
+                let pick = layers::Pick { layer: layer.idx, entry: *idx };
+
+                match areas.entry(pick) {
+                    collections::hash_map::Entry::Occupied(mut temp) => *temp.get_mut() += area,
+                    collections::hash_map::Entry::Vacant(temp) => {
+                        let Some(geojson::JsonValue::String(text)) = entries[*idx].get("NAME") else {
+                            continue;
+                        };
+
+                        temp.insert(area);
+
+                        visible.push(LegendEntry { pick, text: text.to_owned(), color: colors[*idx] });
+                    },
+                }
+            }
+        }
+
+        visible.sort_by(|a, b| areas[&b.pick].total_cmp(&areas[&a.pick]));
+
+        visible.truncate(Self::ENTRIES_MAX);
+
+        self.entries = visible;
+
+        self.refresh(device, queue);
+    }
+
+    fn refresh(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        #[allow(unused_variables)]
+        if let Err(e) = self.prepare(device, queue) {
+            self.atlas.trim();
+
+            #[cfg(feature = "logging")]
+            backend::log::debug!("Failed to prepare legend.\n{e}");
+        }
+    }
+
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<(), glyphon::PrepareError> {
+        use super::timeline;
+
+        let Self {
+            font_system,
+            font_attrs,
+            swash_cache,
+            atlas,
+            buffer,
+            renderer,
+            visible,
+            entries,
+            bounds,
+            screen_resolution: backend::Size { width, height }, ..
+        } = self;
+
+        if !*visible { return Ok(()); }
+
+        let spans = entries
+            .iter()
+            .flat_map(|LegendEntry { text, color: [r, g, b], .. }| [
+                (Self::SWATCH, font_attrs.color(glyphon::Color::rgb(*r, *g, *b))),
+                (text.as_str(), font_attrs.color(Self::COLOR_BASIC)),
+                ("\n", font_attrs.color(Self::COLOR_BASIC)),
+            ]);
+
+        buffer.set_rich_text(font_system, spans, glyphon::Shaping::Basic);
+        buffer.set_size(font_system, Self::WIDTH, *height as f32);
+        buffer.set_wrap(font_system, glyphon::Wrap::None);
+        buffer.shape_until_scroll(font_system);
+
+        let panel_height = entries.len() as f32 * Self::METRICS.line_height;
+
+        // the panel sits in the bottom right corner, above the timeline
+        let left = (*width as f32 - Self::WIDTH - Self::MARGIN).max(0.);
+        let top = (*height as f32 - timeline::Timeline::HEIGHT - Self::MARGIN - panel_height).max(0.);
+
+        *bounds = [left, top, Self::WIDTH, panel_height];
+
+        let region = glyphon::TextArea {
+            buffer,
+            left,
+            top,
+            scale: 1.,
+            bounds: glyphon::TextBounds {
+                left: 0,
+                top: 0,
+                right: *width as i32,
+                bottom: *height as i32,
+            },
+            default_color: Self::COLOR_BASIC,
+        };
+
+        renderer.prepare(
+            device,
+            queue,
+            font_system,
+            atlas,
+            glyphon::Resolution { width: *width, height: *height },
+            Some(region),
+            swash_cache,
+        )
+    }
+
+    pub fn render<'p, 'a: 'p>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'p>,
+    ) -> Result<(), glyphon::RenderError> {
+        let Self { atlas, renderer, visible, .. } = self;
+
+        if *visible { renderer.render(atlas, pass) } else { Ok(()) }
+    }
+}
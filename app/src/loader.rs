@@ -1,13 +1,13 @@
 use backend::wgpu as wgpu;
 
-use super::geom;
+use super::{geom, layers, style, timeline};
 
-use std::{str, fmt, error};
+use std::{str, fmt, error, sync};
 
 #[derive(Debug)]
 pub enum LoaderError {
     InvalidPath(str::Utf8Error),
-    InvalidGeoJson(geojson::Error),
+    InvalidGeoJson(Box<geojson::Error>),
     BrokenGeometry(earcutr::Error),
 }
 
@@ -25,14 +25,20 @@ impl error::Error for LoaderError {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> { None }
 }
 
+// a feature set triangulated away from the event loop, along with its index into the catalog
+type Triangulated = (usize, Result<Vec<geom::FeatureMesh>, LoaderError>);
+
 pub struct FeatureManager {
     idx: usize,
     idx_scroll: usize,
     toggled: bool,
-    feature_paths: &'static [backend::AssetRef<'static>],
+    catalog: &'static [backend::FeatureLayer<'static>],
     slices: u32,
     stacks: u32,
     globe_radius: f32,
+    tolerance: f32,
+    // shared with the feature sets being triangulated
+    style: sync::Arc<style::Style>,
     font_system: glyphon::FontSystem,
     font_attrs: glyphon::Attrs<'static>,
     swash_cache: glyphon::SwashCache,
@@ -40,6 +46,15 @@ pub struct FeatureManager {
     buttons: glyphon::Buffer,
     buttons_width: f32,
     renderer: glyphon::TextRenderer,
+    screen_resolution: backend::Size,
+    timeline: timeline::Timeline,
+    stack: layers::LayerStack,
+    // overlays that have been pinned but not loaded yet
+    // the flag is cleared when the request couldn't be made
+    pending: Vec<(usize, bool)>,
+    // feature sets being triangulated report back through this channel
+    sender: sync::mpsc::Sender<Triangulated>,
+    receiver: sync::mpsc::Receiver<Triangulated>,
 }
 
 impl FeatureManager {
@@ -47,14 +62,34 @@ impl FeatureManager {
 
     const COLOR_FOCUS: glyphon::Color = glyphon::Color::rgb(255, 0, 0);
     const COLOR_BASIC: glyphon::Color = glyphon::Color::rgb(255, 255, 255);
+    const COLOR_PINNED: glyphon::Color = glyphon::Color::rgb(255, 200, 0);
+    const COLOR_HIDDEN: glyphon::Color = glyphon::Color::rgb(128, 128, 128);
+
+    // features triangulated between yields on the web, where there are no worker threads
+    #[cfg(target_arch = "wasm32")]
+    const CHUNK: usize = 64;
 
     pub fn new(
         device: &wgpu::Device, 
         queue: &wgpu::Queue,
         config: crate::Config<'static>,
         font_bytes: std::sync::Arc<Vec<u8>>,
+        timeline_shader: &wgpu::ShaderModule,
+        stack: layers::LayerStack,
         assets: backend::Assets,
-    ) -> Self {
+    ) -> Result<Self, style::StyleError> {
+        let style = backend::Assets::retrieve(config.style_asset_path)
+            .map_err(style::StyleError::MissingAsset)
+            .and_then(style::Style::from_bytes)?;
+
+        let timeline = timeline::Timeline::new(
+            device,
+            queue,
+            config,
+            std::sync::Arc::clone(&font_bytes),
+            timeline_shader,
+        );
+
         let mut font_system = glyphon::FontSystem::new_with_fonts({
             use glyphon::fontdb::Source;
 
@@ -82,19 +117,23 @@ impl FeatureManager {
             }, None
         );
 
-        if assets.request(config.features[0]).is_err() {
+        if assets.request(config.features[0].asset()).is_err() {
             #[cfg(feature = "logging")]
             backend::log::debug!("load interrupted");
         }
 
-        Self {
+        let (sender, receiver) = sync::mpsc::channel();
+
+        Ok(Self {
             idx: 0,
             idx_scroll: 0,
             toggled: true,
-            feature_paths: config.features,
+            catalog: config.features,
             slices: config.slices,
             stacks: config.stacks,
             globe_radius: config.globe_radius,
+            tolerance: config.feature_tolerance,
+            style: sync::Arc::new(style),
             font_system,
             font_attrs,
             swash_cache: glyphon::SwashCache::new(),
@@ -102,6 +141,82 @@ impl FeatureManager {
             buttons,
             buttons_width: f32::MAX,
             renderer,
+            screen_resolution: backend::Size::default(),
+            timeline,
+            stack,
+            pending: Vec::with_capacity(0),
+            sender,
+            receiver,
+        })
+    }
+
+    pub fn stack(&self) -> &layers::LayerStack {
+        &self.stack
+    }
+
+    pub fn stack_mut(&mut self) -> &mut layers::LayerStack {
+        &mut self.stack
+    }
+
+    // years covered by the snapshot shown as the base layer
+    // a snapshot lasts until the next one in the catalog
+    pub fn period(&self) -> Option<[i32; 2]> {
+        let from = self.catalog[self.stack.base()?.idx].year;
+
+        let until = self.catalog
+            .iter()
+            .map(|backend::FeatureLayer { year, .. }| *year)
+            .filter(|year| *year > from)
+            .min()
+            .unwrap_or(i32::MAX);
+
+        Some([from, until])
+    }
+
+    fn request(&mut self, idx: usize, assets: &backend::Assets) {
+        if assets.request(self.catalog[idx].asset()).is_err() {
+            #[cfg(feature = "logging")]
+            backend::log::debug!("load interrupted");
+
+            return;
+        }
+
+        self.idx = idx;
+    }
+
+    fn request_overlay(&mut self, idx: usize, assets: &backend::Assets) -> bool {
+        let requested = assets.request(self.catalog[idx].asset()).is_ok();
+
+        #[cfg(feature = "logging")]
+        if !requested { backend::log::debug!("load interrupted"); }
+
+        requested
+    }
+
+    // pins the given feature set as an overlay, or unpins it if it's already shown
+    fn toggle_overlay(&mut self, idx: usize, assets: &backend::Assets) {
+        if self.stack.remove_overlay(idx) { return; }
+
+        // the base layer already shows it
+        if self.stack.base().is_some_and(|layer| layer.idx == idx) { return; }
+
+        match self.pending.iter().position(|(temp, _)| *temp == idx) {
+            Some(pos) => { self.pending.remove(pos); },
+            None => {
+                let requested = self.request_overlay(idx, assets);
+
+                self.pending.push((idx, requested));
+            },
+        }
+    }
+
+    fn entry_at(&self, backend::Position { x, y }: backend::Position) -> Option<usize> {
+        let temp = (y / Self::METRICS.line_height).floor() as usize;
+
+        match self.buttons.layout_runs().nth(temp) {
+            Some(glyphon::LayoutRun { line_w, .. }) if x < line_w.ceil() => //
+                Some(self.idx_scroll + temp),
+            _ => None,
         }
     }
 
@@ -112,74 +227,101 @@ impl FeatureManager {
         event: backend::AppEvent,
         assets: backend::Assets,
     ) -> bool {
+        if self.timeline.handle_event(device, queue, event) {
+            if let Some(idx) = self.timeline.selection() {
+                if idx != self.idx { self.request(idx, &assets); }
+            }
+
+            self.refresh(device, queue);
+
+            return true;
+        }
+
         match event {
             backend::AppEvent::Resized(size) => {
-                let line_maxima = (size.height as f32 / Self::METRICS.line_height).floor() as usize;
+                self.screen_resolution = size;
+
+                let line_maxima = (Self::pane_height(size) / Self::METRICS.line_height).floor() as usize;
 
-                if self.feature_paths.len() - self.idx_scroll < line_maxima {
-                    if let Some(idx_temp) = self.feature_paths.len().checked_sub(line_maxima) {
+                if self.catalog.len() - self.idx_scroll < line_maxima {
+                    if let Some(idx_temp) = self.catalog.len().checked_sub(line_maxima) {
                         self.idx_scroll = idx_temp;
                     }
                 }
 
-                #[allow(unused_variables)]
-                if let Err(e) = self.prepare(device, queue, size) {
-                    self.atlas.trim();
-
-                    #[cfg(feature = "logging")] 
-                    backend::log::debug!("Failed to prepare layer selection pane.\n{e}");
-                }
+                self.refresh(device, queue);
 
                 // other components need to process size changes
                 false
             },
             backend::AppEvent::MouseScroll { delta, cursor } if self.toggled && cursor.x < self.buttons_width => {
-                let (width, height) = self.buttons.size();
+                let (_, height) = self.buttons.size();
 
                 let line_maxima = (height / Self::METRICS.line_height).floor() as usize;
                 
-                if delta > 0. && (self.feature_paths.len() - self.idx_scroll) > line_maxima {
+                if delta > 0. && (self.catalog.len() - self.idx_scroll) > line_maxima {
                     self.idx_scroll += 1;
                 } else if delta < 0. && self.idx_scroll > 0 {
                     self.idx_scroll -= 1;
                 }
 
-                let screen_resolution = backend::Size { 
-                    width: width as u32, 
-                    height: height as u32,
-                };
-
-                #[allow(unused_variables)]
-                if let Err(e) = self.prepare(device, queue, screen_resolution) {
-                    self.atlas.trim();
-
-                    #[cfg(feature = "logging")] 
-                    backend::log::debug!("Failed to prepare layer selection pane.\n{e}");
-                }
+                self.refresh(device, queue);
 
                 self.buttons.shape_until_scroll(&mut self.font_system);
 
                 true
             }
             backend::AppEvent::Mouse { 
-                button: backend::event::MouseButton::Left, 
+                button, 
                 state: backend::event::ElementState::Pressed, 
-                cursor: backend::Position { x, y },
-            } if self.toggled => {
-                let temp = (y / Self::METRICS.line_height).floor() as usize;
-                match self.buttons.layout_runs().nth(temp) {
-                    Some(glyphon::LayoutRun { line_w, .. }) if x < line_w.ceil() => {
-                        self.idx = self.idx_scroll + temp;
-
-                        if assets.request(self.feature_paths[self.idx]).is_err() {
-                            #[cfg(feature = "logging")]
-                            backend::log::debug!("load interrupted");
-                        }
-
-                        true
-                    }, _ => false,
+                cursor,
+            } if self.toggled => match (button, self.entry_at(cursor)) {
+                (backend::event::MouseButton::Left, Some(idx)) => {
+                    self.timeline.seek_to_feature(idx);
+                    self.timeline.refresh(device, queue);
+
+                    self.request(idx, &assets);
+                    self.refresh(device, queue);
+
+                    true
+                },
+                (backend::event::MouseButton::Right, Some(idx)) => {
+                    self.toggle_overlay(idx, &assets);
+                    self.refresh(device, queue);
+
+                    true
+                },
+                (backend::event::MouseButton::Middle, Some(idx)) => {
+                    if self.stack.select(idx) {
+                        self.refresh(device, queue);
+                    }
+
+                    true
+                },
+                _ => false,
+            },
+            backend::AppEvent::Key { 
+                code: code @ (backend::event::KeyCode::BracketLeft | backend::event::KeyCode::BracketRight), 
+                state: backend::event::ElementState::Pressed,
+            } => {
+                let increase = matches!(code, backend::event::KeyCode::BracketRight);
+
+                self.stack.step_opacity(queue, increase)
+            },
+            backend::AppEvent::Key { 
+                code: backend::event::KeyCode::KeyH, 
+                state: backend::event::ElementState::Pressed,
+            } => {
+                if self.stack.toggle_visibility() {
+                    self.refresh(device, queue);
                 }
+
+                true
             },
+            backend::AppEvent::Key { 
+                code: code @ (backend::event::KeyCode::Comma | backend::event::KeyCode::Period), 
+                state: backend::event::ElementState::Pressed,
+            } => self.stack.move_selected(matches!(code, backend::event::KeyCode::Period)),
             backend::AppEvent::Key { 
                 code: backend::event::KeyCode::Tab, 
                 state: backend::event::ElementState::Released,
@@ -188,37 +330,77 @@ impl FeatureManager {
         }
     }
 
+    // the pane stops short of the timeline along the bottom of the screen
+    fn pane_height(screen_resolution: backend::Size) -> f32 {
+        (screen_resolution.height as f32 - timeline::Timeline::HEIGHT).max(0.)
+    }
+
+    fn refresh(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        #[allow(unused_variables)]
+        if let Err(e) = self.prepare(device, queue) {
+            self.atlas.trim();
+
+            #[cfg(feature = "logging")] 
+            backend::log::debug!("Failed to prepare layer selection pane.\n{e}");
+        }
+    }
+
     fn prepare(
         &mut self,
         device: &wgpu::Device, 
         queue: &wgpu::Queue,
-        backend::Size { width, height }: backend::Size,
     ) -> Result<(), glyphon::PrepareError> {
+        let pane_height = Self::pane_height(self.screen_resolution);
+
         let Self {
             idx,
             idx_scroll,
-            feature_paths,
+            catalog,
+            stack,
+            pending,
             buttons,
             buttons_width,
             font_system, 
             font_attrs, 
             atlas,
             swash_cache,
-            renderer, ..
+            renderer, 
+            screen_resolution: backend::Size { width, height }, ..
         } = self;
 
-        let spans = feature_paths
+        let (width, height) = (*width, *height);
+
+        let spans = catalog
             .iter()
             .copied()
             .enumerate()
             .skip(*idx_scroll)
-            .map(|(temp, backend::AssetRef { path, .. })| {
-                let color = if *idx == temp {
-                    Self::COLOR_FOCUS
-                } else {
-                    Self::COLOR_BASIC
-                }; (path, font_attrs.color(color))
-            }).flat_map(|a| [a, ("\n", font_attrs.color(Self::COLOR_BASIC))]);
+            .flat_map(|(temp, backend::FeatureLayer { label, .. })| {
+                let pinned = pending.iter().any(|(pending, _)| *pending == temp);
+
+                let base = stack.base().filter(|layer| layer.idx == temp);
+
+                let color = match (stack.overlay(temp), base) {
+                    (Some(layers::Layer { visible: true, .. }), _) => Self::COLOR_PINNED,
+                    (Some(layers::Layer { visible: false, .. }), _) => Self::COLOR_HIDDEN,
+                    (None, _) if pinned => Self::COLOR_PINNED,
+                    (None, Some(layers::Layer { visible: false, .. })) => Self::COLOR_HIDDEN,
+                    (None, _) if *idx == temp => Self::COLOR_FOCUS,
+                    (None, _) => Self::COLOR_BASIC,
+                };
+
+                // marks the layer the opacity, visibility and order keys act on
+                let marker = match stack.selected() {
+                    Some(layer) if layer.idx == temp => "> ",
+                    _ => "",
+                };
+
+                [
+                    (marker, font_attrs.color(Self::COLOR_FOCUS)),
+                    (label, font_attrs.color(color)),
+                    ("\n", font_attrs.color(Self::COLOR_BASIC)),
+                ]
+            });
 
         buttons.set_rich_text(
             font_system, 
@@ -226,7 +408,7 @@ impl FeatureManager {
             glyphon::Shaping::Basic,
         );
 
-        buttons.set_size(font_system, width as f32, height as f32);
+        buttons.set_size(font_system, width as f32, pane_height);
 
         buttons.shape_until_scroll(font_system);
 
@@ -248,7 +430,7 @@ impl FeatureManager {
                 left: 0,
                 top: 0,
                 right: width as i32,
-                bottom: height as i32,
+                bottom: pane_height as i32,
             },
             default_color: glyphon::Color::rgb(255, 255, 255),
         };
@@ -264,46 +446,212 @@ impl FeatureManager {
         )
     }
 
+    // whether a feature set wanted at the given index is still wanted
+    // along with its position among the pending overlays if it's one of them
+    fn wanted(&self, idx: usize) -> Option<Option<usize>> {
+        let pending = self.pending.iter().position(|(temp, _)| *temp == idx);
+
+        (pending.is_some() || idx == self.idx).then_some(pending)
+    }
+
+    // starts triangulating a feature set that has finished downloading
+    // the result is picked up by FeatureManager::poll
     pub fn load(
         &mut self, 
-        device: &wgpu::Device, 
-        queue: &wgpu::Queue,
+        assets: backend::Assets,
         bytes: &[u8],
-        screen_resolution: backend::Size,
-    ) -> Result<geom::Geometry<geom::FeatureVertex, geom::FeatureMetadata>, LoaderError> {
-        let Self {
-            slices,
-            stacks,
-            globe_radius, ..
-        } = self;
+        asset_path: &str,
+    ) {
+        // on the web, the asset path is the full url
+        let idx = self.catalog
+            .iter()
+            .position(|backend::FeatureLayer { path, .. }| asset_path.ends_with(path));
+
+        // skip feature sets that were deselected while they were loading
+        let Some(idx) = idx.filter(|idx| self.wanted(*idx).is_some()) else { return; };
 
+        self.spawn(idx, bytes.to_vec(), assets.waker());
+
+        // retry overlays that were pinned while another feature set was loading
+        for pos in 0..self.pending.len() {
+            if let (idx, false) = self.pending[pos] {
+                self.pending[pos].1 = self.request_overlay(idx, &assets);
+            }
+        }
+    }
+
+    // the part of loading that doesn't need the gpu
+    fn parse(bytes: &[u8]) -> Result<Vec<geojson::Feature>, LoaderError> {
         let features = str::from_utf8(bytes)
             .map_err(LoaderError::InvalidPath)?
             .parse::<geojson::GeoJson>()
-            .map_err(LoaderError::InvalidGeoJson)?;
+            .map_err(|e| LoaderError::InvalidGeoJson(Box::new(e)))?;
 
         let geojson::FeatureCollection { 
             features, .. 
         } = geojson::FeatureCollection::try_from(features)
-            .map_err(LoaderError::InvalidGeoJson)?;
+            .map_err(|e| LoaderError::InvalidGeoJson(Box::new(e)))?;
 
-        let geometry = geom::Geometry::build_feature_geometry(
-            device, 
-            features.as_slice(),
-            *slices, 
-            *stacks,
-            *globe_radius, 
-        ).map_err(LoaderError::BrokenGeometry)?;
+        Ok(features)
+    }
 
-        #[allow(unused_variables)]
-        if let Err(e) = self.prepare(device, queue, screen_resolution) {
-            self.atlas.trim();
-            
-            #[cfg(feature = "logging")] 
-            backend::log::debug!("Failed to prepare layer selection pane.\n{e}");
+    // parses and triangulates on a worker thread
+    // the event loop is woken once the result has been sent
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn(&self, idx: usize, bytes: Vec<u8>, waker: backend::Waker) {
+        let Self { slices, stacks, globe_radius, tolerance, .. } = *self;
+
+        let style = sync::Arc::clone(&self.style);
+        let sender = self.sender.clone();
+
+        std::thread::spawn(move || {
+            let meshes = Self::parse(&bytes).and_then(|features| {
+                geom::FeatureMeshBuilder::new(features, slices, stacks, globe_radius, tolerance)
+                    .build(&style)
+                    .map_err(LoaderError::BrokenGeometry)
+            });
+
+            // the receiver only goes away with the app
+            let _ = sender.send((idx, meshes));
+
+            waker.wake();
+        });
+    }
+
+    // parses and triangulates in a task that gives way to the browser between chunks
+    #[cfg(target_arch = "wasm32")]
+    fn spawn(&self, idx: usize, bytes: Vec<u8>, waker: backend::Waker) {
+        use backend::web;
+
+        let Self { slices, stacks, globe_radius, tolerance, .. } = *self;
+
+        let style = sync::Arc::clone(&self.style);
+        let sender = self.sender.clone();
+
+        web::wasm_bindgen_futures::spawn_local(async move {
+            let meshes = async {
+                let features = Self::parse(&bytes)?;
+
+                // parsing alone can take a while
+                web::yield_now().await;
+
+                let mut builder = geom::FeatureMeshBuilder::new(features, slices, stacks, globe_radius, tolerance);
+
+                while !builder.step(Self::CHUNK, &style).map_err(LoaderError::BrokenGeometry)? {
+                    web::yield_now().await;
+                }
+
+                Ok::<_, LoaderError>(builder.finish())
+            }.await;
+
+            // the receiver only goes away with the app
+            let _ = sender.send((idx, meshes));
+
+            waker.wake();
+        });
+    }
+
+    // steps timeline playback by `dt` seconds, returns true while it's playing
+    // playback holds on a snapshot until it has been loaded
+    pub fn tick(
+        &mut self, 
+        device: &wgpu::Device, 
+        queue: &wgpu::Queue,
+        assets: &backend::Assets,
+        dt: f32,
+    ) -> bool {
+        if !self.timeline.playing() { return false; }
+
+        let loaded = self.stack.base().is_some_and(|layer| layer.idx == self.idx);
+
+        if !loaded || !self.timeline.tick(dt) { return true; }
+
+        self.timeline.refresh(device, queue);
+
+        if let Some(idx) = self.timeline.selection() {
+            if idx != self.idx { self.request(idx, assets); }
+        }
+
+        self.refresh(device, queue);
+
+        // the last snapshot may have just been reached
+        self.timeline.playing()
+    }
+
+    // forgets a feature set that couldn't be loaded
+    // the slider goes back to the snapshot on screen so playback doesn't wait on it
+    fn discard(&mut self, idx: usize) {
+        if let Some(pos) = self.pending.iter().position(|(temp, _)| *temp == idx) {
+            self.pending.remove(pos);
+        }
+
+        if idx != self.idx { return; }
+
+        self.timeline.pause();
+
+        if let Some(base) = self.stack.base().map(|layer| layer.idx) {
+            self.idx = base;
+
+            self.timeline.seek_to_feature(base);
+        }
+    }
+
+    // moves feature sets that have finished triangulating to the gpu
+    // returns true if a layer was added, replaced or given up on
+    pub fn poll(
+        &mut self, 
+        device: &wgpu::Device, 
+        queue: &wgpu::Queue,
+        assets: &backend::Assets,
+    ) -> bool {
+        let mut loaded = false;
+
+        while let Ok((idx, meshes)) = self.receiver.try_recv() {
+            let meshes = match meshes {
+                Ok(meshes) => meshes,
+                #[allow(unused_variables)]
+                Err(e) => {
+                    #[cfg(feature = "logging")] 
+                    backend::log::debug!("Failed to parse feature [{}].\n{e}", self.catalog[idx].path);
+
+                    self.discard(idx);
+                    self.timeline.refresh(device, queue);
+
+                    loaded = true;
+
+                    continue;
+                },
+            };
+
+            // the feature set may have been deselected while it was triangulated
+            let Some(pending) = self.wanted(idx) else { continue; };
+
+            let levels = meshes
+                .into_iter()
+                .map(|mesh| mesh.upload(device))
+                .collect();
+
+            match pending {
+                Some(pos) => {
+                    self.pending.remove(pos);
+                    self.stack.insert_overlay(device, queue, idx, levels);
+                },
+                None => self.stack.replace_base(device, queue, idx, levels),
+            }
+
+            loaded = true;
         }
 
-        Ok(geometry)
+        if !loaded { return false; }
+
+        // the slider may have moved on while this feature set was loading
+        if let Some(idx) = self.timeline.selection() {
+            if idx != self.idx { self.request(idx, assets); }
+        }
+
+        self.refresh(device, queue);
+
+        true
     }
 
     pub fn render<'p, 'a: 'p>(
@@ -314,9 +662,12 @@ impl FeatureManager {
         let Self { 
             toggled,
             atlas, 
-            renderer, .. 
+            renderer, 
+            timeline, .. 
         } = self;
 
-        if *toggled { renderer.render(atlas, pass) } else { Ok(()) }
+        if *toggled { renderer.render(atlas, pass)?; }
+
+        timeline.render(pass)
     }
 }
\ No newline at end of file
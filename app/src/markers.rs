@@ -0,0 +1,266 @@
+use backend::wgpu as wgpu;
+
+use super::{camera, geom, util};
+
+use std::{str, fmt, error};
+
+#[derive(Debug)]
+pub enum MarkerError {
+    MissingAsset(std::io::Error),
+    InvalidEncoding(str::Utf8Error),
+    InvalidGeoJson(Box<geojson::Error>),
+    // the marker at this index isn't a point or has no START year
+    InvalidMarker(usize),
+}
+
+impl fmt::Display for MarkerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkerError::MissingAsset(err) => write!(f, "{}", err),
+            MarkerError::InvalidEncoding(err) => write!(f, "{}", err),
+            MarkerError::InvalidGeoJson(err) => write!(f, "{}", err),
+            MarkerError::InvalidMarker(idx) => write!(f, "marker {} is invalid", idx),
+        }
+    }
+}
+
+impl error::Error for MarkerError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> { None }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Debug)]
+pub struct MarkerUniform {
+    // markers overlapping [from, until) are shown
+    pub period: [i32; 2],
+    // index of the marker under the cursor, u32::MAX if there is none
+    pub hovered: u32,
+    // diameter in physical pixels
+    pub size: f32,
+}
+
+struct Marker {
+    entry: geojson::JsonObject,
+    pos: [f32; 3],
+    years: [i32; 2],
+}
+
+// cities, battles and events drawn as billboards on top of the feature layers
+// a marker is shown while its date range overlaps the period of the base snapshot
+//
+// markers are read from a collection of points with the properties
+// NAME, KIND, START, END and DESCRIPTION
+// markers without an END year stay on the globe from START onwards
+pub struct Markers {
+    markers: Vec<Marker>,
+    pub geometry: geom::Geometry<geom::MarkerVertex, ()>,
+    uniform_buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+    period: Option<[i32; 2]>,
+    hovered: Option<usize>,
+}
+
+impl Markers {
+    const SIZE: f32 = 14.;
+
+    // colors of the known marker kinds, others are hashed
+    const KINDS: &'static [(&'static str, [u8; 3])] = &[
+        ("city", [255, 255, 255]),
+        ("battle", [220, 50, 47]),
+        ("event", [38, 139, 210]),
+    ];
+
+    pub fn new(
+        device: &wgpu::Device,
+        bytes: &[u8],
+        globe_radius: f32,
+    ) -> Result<Self, MarkerError> {
+        use geojson::JsonValue;
+
+        let markers = str::from_utf8(bytes)
+            .map_err(MarkerError::InvalidEncoding)?
+            .parse::<geojson::GeoJson>()
+            .map_err(|e| MarkerError::InvalidGeoJson(Box::new(e)))?;
+
+        let geojson::FeatureCollection {
+            features, ..
+        } = geojson::FeatureCollection::try_from(markers)
+            .map_err(|e| MarkerError::InvalidGeoJson(Box::new(e)))?;
+
+        fn year(entry: &geojson::JsonObject, key: &str) -> Option<i32> {
+            match entry.get(key) {
+                Some(JsonValue::Number(year)) => year.as_i64().map(|year| year as i32),
+                Some(JsonValue::String(year)) => year.parse().ok(),
+                _ => None,
+            }
+        }
+
+        let mut markers = Vec::with_capacity(features.len());
+        let mut shapes = Vec::with_capacity(features.len());
+
+        for (idx, feature) in features.into_iter().enumerate() {
+            let geojson::Feature { geometry, properties, .. } = feature;
+
+            let (Some(geojson::Value::Point(pt)), Some(entry)) = ( //
+                geometry.map(|geojson::Geometry { value, .. }| value), properties
+            ) else {
+                return Err(MarkerError::InvalidMarker(idx));
+            };
+
+            let (Some(lon), Some(lat), Some(start)) = ( //
+                pt.first(), pt.get(1), year(&entry, "START")
+            ) else {
+                return Err(MarkerError::InvalidMarker(idx));
+            };
+
+            let years = [start, year(&entry, "END").unwrap_or(i32::MAX)];
+
+            let pos = util::lat_lon_to_vertex([*lat as f32, *lon as f32], globe_radius + 1.);
+
+            let color = match entry.get("KIND") {
+                Some(JsonValue::String(kind)) => Self::KINDS
+                    .iter()
+                    .find(|(temp, _)| temp == kind)
+                    .map(|(_, color)| *color)
+                    .unwrap_or_else(|| util::str_to_rgb8(kind)),
+                _ => Self::KINDS[0].1,
+            };
+
+            shapes.push((pos, color, years));
+
+            markers.push(Marker { entry, pos, years });
+        }
+
+        let geometry = geom::Geometry::build_marker_geometry(device, &shapes);
+
+        let bind_group_layout = device.create_bind_group_layout(&{
+            wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            }
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: std::mem::size_of::<MarkerUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&{
+            wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                }],
+            }
+        });
+
+        Ok(Self {
+            markers,
+            geometry,
+            uniform_buffer,
+            bind_group_layout,
+            bind_group,
+            period: None,
+            hovered: None,
+        })
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    // markers are hidden until a snapshot has been loaded
+    pub fn active(&self) -> bool {
+        self.period.is_some()
+    }
+
+    pub fn entry(&self, idx: usize) -> Option<&geojson::JsonObject> {
+        self.markers.get(idx).map(|Marker { entry, .. }| entry)
+    }
+
+    fn write_uniform(&self, queue: &wgpu::Queue) {
+        let uniform = MarkerUniform {
+            period: self.period.unwrap_or([0; 2]),
+            hovered: self.hovered.map(|idx| idx as u32).unwrap_or(u32::MAX),
+            size: Self::SIZE,
+        };
+
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    pub fn set_period(&mut self, queue: &wgpu::Queue, period: Option<[i32; 2]>) {
+        self.period = period;
+
+        // the hovered marker may have just disappeared
+        self.hovered = self.hovered.filter(|idx| self.shown(*idx));
+
+        self.write_uniform(queue);
+    }
+
+    // returns true if the hovered marker changed
+    pub fn set_hovered(&mut self, queue: &wgpu::Queue, hovered: Option<usize>) -> bool {
+        if self.hovered == hovered { return false; }
+
+        self.hovered = hovered;
+
+        self.write_uniform(queue);
+
+        true
+    }
+
+    fn shown(&self, idx: usize) -> bool {
+        let Some([from, until]) = self.period else { return false; };
+
+        let [start, end] = self.markers[idx].years;
+
+        start < until && end >= from
+    }
+
+    // finds the shown marker closest to the eye along the given ray
+    pub fn pick(
+        &self,
+        camera_uniform: camera::CameraUniform,
+        ray: [f32; 3],
+    ) -> Option<usize> {
+        let camera::CameraUniform { eye, proj, viewport, .. } = camera_uniform;
+
+        let eye_pos = ultraviolet::Vec4::from(eye).xyz();
+
+        let mut nearest = (f32::MAX, None);
+
+        for (idx, Marker { pos, .. }) in self.markers.iter().enumerate() {
+            if !self.shown(idx) { continue; }
+
+            let center = ultraviolet::Vec3::from(pos);
+
+            // skip markers on the far side of the globe
+            if center.dot(eye_pos - center) <= 0. { continue; }
+
+            let dist = (center - eye_pos).mag();
+
+            // the world-space radius that covers the billboard at this distance
+            let radius = dist * Self::SIZE / (proj[1][1] * viewport[1]);
+
+            if util::intrs_sphere(eye, ray, *pos, radius) && dist < nearest.0 {
+                nearest = (dist, Some(idx));
+            }
+        }
+
+        nearest.1
+    }
+}
@@ -4,11 +4,27 @@ mod util;
 mod camera;
 mod map_tex;
 mod loader;
+mod feature_info;
+mod layers;
+mod timeline;
+mod search;
+mod depth;
+mod style;
+mod legend;
+mod markers;
+mod routes;
 
 use backend::wgpu as wgpu;
 
 use std::{mem, sync};
 
+#[derive(Clone, Copy)]
+pub struct Border {
+    // in physical pixels
+    pub width: f32,
+    pub color: [f32; 4],
+}
+
 #[derive(Clone, Copy)]
 pub struct Config<'a> {
     pub surface_format: wgpu::TextureFormat,
@@ -20,8 +36,27 @@ pub struct Config<'a> {
     pub globe_shader_asset_path: &'a str,
     pub basemap: &'a str,
     pub basemap_padding: backend::Size,
-    pub features: &'a [backend::AssetRef<'a>],
+    pub features: &'a [backend::FeatureLayer<'a>],
     pub features_shader_asset_path: &'a str,
+    // greatest distance in world units between a feature's triangles and the sphere
+    // smaller values subdivide features more finely
+    pub feature_tolerance: f32,
+    // opacity of the feature set selected on the timeline
+    pub feature_opacity: f32,
+    // initial opacity of feature sets pinned on top of it
+    pub overlay_opacity: f32,
+    pub outlines_shader_asset_path: &'a str,
+    pub feature_border: Border,
+    pub overlay_border: Border,
+    // rules for coloring features, see style::Style
+    pub style_asset_path: &'a str,
+    // cities, battles and events, see markers::Markers
+    pub markers_asset_path: &'a str,
+    pub markers_shader_asset_path: &'a str,
+    // migrations and trade routes, see routes::Routes
+    pub routes_asset_path: &'a str,
+    pub routes_shader_asset_path: &'a str,
+    pub timeline_shader_asset_path: &'a str,
     // the number of rays to distribute across the screen's width
     // vertical ray density is proportional to the window's aspect ratio
     pub feature_label_ray_density: u32,
@@ -43,10 +78,20 @@ pub struct App {
     globe_radius: f32,
     globe: geom::Geometry<geom::GlobeVertex, ()>,
     globe_pipeline: wgpu::RenderPipeline,
+    // created once the surface size is known
+    depth: Option<depth::DepthTexture>,
     features: loader::FeatureManager,
-    feature_geometry: geom::Geometry<geom::FeatureVertex, geom::FeatureMetadata>,
     feature_pipeline: wgpu::RenderPipeline,
+    outline_pipeline: wgpu::RenderPipeline,
+    markers: markers::Markers,
+    marker_pipeline: wgpu::RenderPipeline,
+    routes: routes::Routes,
+    route_pipeline: wgpu::RenderPipeline,
     feature_labels: feature_labels::LabelEngine,
+    feature_info: feature_info::FeatureInfo,
+    search: search::Search,
+    legend: legend::Legend,
+    selected: Option<layers::Pick>,
     screen_ray_density: u32,
     screen_rays: Vec<[f32; 3]>,
     screen_resolution: backend::Size,
@@ -228,7 +273,7 @@ impl backend::App for App {
                         })
                     ],
                 }),
-                depth_stencil: None,
+                depth_stencil: Some(depth::DepthTexture::stencil_state(wgpu::CompareFunction::Less)),
                 multiview: None,
                 multisample: wgpu::MultisampleState {
                     count: 1,
@@ -247,10 +292,19 @@ impl backend::App for App {
             }
         });
 
+        let feature_layers = layers::LayerStack::new(
+            device,
+            queue,
+            config.feature_opacity,
+            config.overlay_opacity,
+            config.feature_border,
+            config.overlay_border,
+        );
+
         let feature_pipeline_layout = device.create_pipeline_layout(&{
             wgpu::PipelineLayoutDescriptor {
                 label: None,
-                bind_group_layouts: &[&camera_bind_group_layout],
+                bind_group_layouts: &[&camera_bind_group_layout, feature_layers.bind_group_layout()],
                 push_constant_ranges: &[],
             }
         });
@@ -279,7 +333,8 @@ impl backend::App for App {
                         })
                     ],
                 }),
-                depth_stencil: None,
+                // overlays share depth with the layers beneath them
+                depth_stencil: Some(depth::DepthTexture::stencil_state(wgpu::CompareFunction::LessEqual)),
                 multiview: None,
                 multisample: wgpu::MultisampleState {
                     count: 1,
@@ -298,6 +353,161 @@ impl backend::App for App {
             }
         }); 
 
+        let outline_pipeline_shader = device.create_shader_module({
+            (util::load_shader(config.outlines_shader_asset_path).await)?
+        });
+
+        let outline_pipeline = device.create_render_pipeline(&{
+            wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&feature_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &outline_pipeline_shader,
+                    entry_point: "vertex",
+                    buffers: &[geom::OutlineVertex::layout()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &outline_pipeline_shader,
+                    entry_point: "fragment",
+                    targets: &[
+                        Some(wgpu::ColorTargetState {
+                            format: config.surface_format,
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })
+                    ],
+                }),
+                depth_stencil: Some(depth::DepthTexture::stencil_state(wgpu::CompareFunction::LessEqual)),
+                multiview: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    // quads face either way depending on the segment's direction
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                },
+            }
+        });
+
+        let markers = backend::Assets::retrieve(config.markers_asset_path)
+            .map_err(markers::MarkerError::MissingAsset)
+            .and_then(|bytes| markers::Markers::new(device, bytes, config.globe_radius))?;
+
+        let marker_pipeline_layout = device.create_pipeline_layout(&{
+            wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&camera_bind_group_layout, markers.bind_group_layout()],
+                push_constant_ranges: &[],
+            }
+        });
+
+        let marker_pipeline_shader = device.create_shader_module({
+            (util::load_shader(config.markers_shader_asset_path).await)?
+        });
+
+        let marker_pipeline = device.create_render_pipeline(&{
+            wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&marker_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &marker_pipeline_shader,
+                    entry_point: "vertex",
+                    buffers: &[geom::MarkerVertex::layout()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &marker_pipeline_shader,
+                    entry_point: "fragment",
+                    targets: &[
+                        Some(wgpu::ColorTargetState {
+                            format: config.surface_format,
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })
+                    ],
+                }),
+                depth_stencil: Some(depth::DepthTexture::stencil_state(wgpu::CompareFunction::LessEqual)),
+                multiview: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    // billboards always face the camera
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                },
+            }
+        });
+
+        let routes = backend::Assets::retrieve(config.routes_asset_path)
+            .map_err(routes::RouteError::MissingAsset)
+            .and_then(|bytes| routes::Routes::new(device, queue, bytes, config.globe_radius))?;
+
+        let route_pipeline_layout = device.create_pipeline_layout(&{
+            wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&camera_bind_group_layout, routes.bind_group_layout()],
+                push_constant_ranges: &[],
+            }
+        });
+
+        let route_pipeline_shader = device.create_shader_module({
+            (util::load_shader(config.routes_shader_asset_path).await)?
+        });
+
+        let route_pipeline = device.create_render_pipeline(&{
+            wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&route_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &route_pipeline_shader,
+                    entry_point: "vertex",
+                    buffers: &[geom::RouteVertex::layout()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &route_pipeline_shader,
+                    entry_point: "fragment",
+                    targets: &[
+                        Some(wgpu::ColorTargetState {
+                            format: config.surface_format,
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })
+                    ],
+                }),
+                depth_stencil: Some(depth::DepthTexture::stencil_state(wgpu::CompareFunction::LessEqual)),
+                multiview: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                },
+            }
+        });
+
         let feature_label_font_bytes = sync::Arc::new({
             backend::Assets::retrieve(config.font_asset_path)?.to_vec()
         });
@@ -310,13 +520,43 @@ impl backend::App for App {
             config.font_family,
         );
 
+        let feature_info = feature_info::FeatureInfo::new(
+            device,
+            queue,
+            config.surface_format,
+            sync::Arc::clone(&feature_label_font_bytes),
+            config.font_family,
+        );
+
+        let search = search::Search::new(
+            device,
+            queue,
+            config.surface_format,
+            sync::Arc::clone(&feature_label_font_bytes),
+            config.font_family,
+        );
+
+        let legend = legend::Legend::new(
+            device,
+            queue,
+            config.surface_format,
+            sync::Arc::clone(&feature_label_font_bytes),
+            config.font_family,
+        );
+
+        let timeline_shader = device.create_shader_module({
+            (util::load_shader(config.timeline_shader_asset_path).await)?
+        });
+
         let features = loader::FeatureManager::new(
             device,
             queue,
             config,
             sync::Arc::clone(&feature_label_font_bytes),
+            &timeline_shader,
+            feature_layers,
             assets,
-        );
+        )?;
 
         Ok(Self {
             texture,
@@ -327,10 +567,19 @@ impl backend::App for App {
             globe_radius: config.globe_radius,
             globe,
             globe_pipeline,
+            depth: None,
             features,
-            feature_geometry: geom::Geometry::empty(device),
             feature_pipeline,
+            outline_pipeline,
+            markers,
+            marker_pipeline,
+            routes,
+            route_pipeline,
             feature_labels,
+            feature_info,
+            search,
+            legend,
+            selected: None,
             screen_ray_density: config.feature_label_ray_density,
             screen_rays: Vec::with_capacity(0),
             screen_resolution: backend::Size::default(),
@@ -342,9 +591,13 @@ impl backend::App for App {
         encoder: &mut wgpu::CommandEncoder,
         surface: &wgpu::TextureView,
     ) -> Result<(), Self::SubmissionError> {       
-        self.submit_globe_pass(encoder, surface);
+        if let Some(depth) = self.depth.as_ref() {
+            self.submit_globe_pass(encoder, surface, depth);
 
-        self.submit_feature_pass(encoder, surface)?;
+            self.submit_feature_pass(encoder, surface, depth);
+        }
+
+        self.submit_overlay_pass(encoder, surface)?;
 
         Ok(())
     }
@@ -357,26 +610,225 @@ impl backend::App for App {
     ) -> bool {
         let Self {
             camera,
-            camera_buffer,
             globe_radius,
+            depth,
             features, 
-            feature_geometry, 
-            feature_labels,
-            screen_ray_density, 
-            screen_rays,
+            markers,
+            routes,
+            feature_info,
+            search,
+            legend,
+            selected,
             screen_resolution, ..
         } = self;
 
         match event {
+            event if search.handle_event(device, queue, event, features.stack()) => {
+                let Some(pick) = search.take_choice() else { return true; };
+
+                Self::focus(device, queue, camera, features.stack(), feature_info, selected, pick);
+            },
+            event if legend.handle_event(device, queue, event) => {
+                // falls through so the legend is filled when it's first shown
+                if let Some(pick) = legend.take_choice() {
+                    Self::focus(device, queue, camera, features.stack(), feature_info, selected, pick);
+                }
+            },
             event if features.handle_event(device, queue, event, assets) => { return true; }
-            backend::AppEvent::Resized(size) => { *screen_resolution = size; },
-            event if !camera.handle_event(event) => { return false; },
+            event if routes.handle_event(event) => { return true; },
+            backend::AppEvent::Resized(size) => { 
+                *screen_resolution = size; 
+
+                if let Some(depth) = depth.replace(depth::DepthTexture::new(device, size)) {
+                    depth.destroy();
+                }
+
+                feature_info.resize(device, queue, size);
+            },
+            backend::AppEvent::CursorMoved { cursor } => {
+                // picking is skipped while the globe is in motion
+                if camera.movement_in_progress() { return false; }
+
+                let marker = Self::pick_marker(camera, markers, *screen_resolution, cursor);
+
+                // markers are drawn over the features beneath them
+                let hovered = match marker {
+                    Some(_) => None,
+                    None => Self::pick_feature(
+                        camera, 
+                        features.stack(), 
+                        *screen_resolution, 
+                        *globe_radius, 
+                        cursor,
+                    ),
+                };
+
+                let marker_changed = markers.set_hovered(queue, marker);
+
+                return features.stack_mut().set_hovered(queue, hovered) || marker_changed;
+            },
+            event if !camera.handle_event(event, *screen_resolution) => { return false; },
+            backend::AppEvent::Mouse { 
+                button: backend::event::MouseButton::Left, 
+                state: backend::event::ElementState::Released, 
+                cursor,
+            } if camera.clicked() => {
+                if let Some(idx) = Self::pick_marker(camera, markers, *screen_resolution, cursor) {
+                    *selected = None;
+
+                    if let Some(entry) = markers.entry(idx) {
+                        feature_info.show_marker(device, queue, entry);
+                    }
+
+                    return true;
+                }
+
+                self.selected = self.pick(cursor);
+
+                let entry = self.selected.and_then(|pick| self.features.stack().entry(pick));
+
+                self.feature_info.show(device, queue, entry);
+            },
             _ => { /*  */ },
         }
 
-        let camera_uniform = camera
-            .update()
-            .build_camera_uniform(*screen_resolution);
+        self.refresh_view(device, queue);
+
+        true
+    }
+
+    fn tick(
+        &mut self, 
+        device: &wgpu::Device, 
+        queue: &wgpu::Queue, 
+        assets: backend::Assets,
+        dt: f32,
+    ) -> bool {
+        // feature sets triangulated in the background are uploaded between frames
+        let loaded = self.features.poll(device, queue, &assets);
+
+        if loaded { self.refresh_layers(device, queue); }
+
+        let playing = self.features.tick(device, queue, &assets, dt);
+
+        // feature sets still being triangulated wake the event loop once they're done
+        let animating = self.routes.tick(queue, dt) || playing || loaded;
+
+        if !self.camera.update(dt) { return animating; }
+
+        self.refresh_view(device, queue);
+
+        true
+    }
+    
+    fn update(
+        &mut self, 
+        #[allow(unused_variables)]
+        device: &wgpu::Device,
+        #[allow(unused_variables)]
+        queue: &wgpu::Queue,
+        assets: backend::Assets,
+        bytes: &[u8],
+        asset_path: &str,
+    ) -> Result<(), Self::UpdateError> {
+        // parsing and triangulation happen away from the event loop
+        // the layer is swapped in by App::tick once it's ready
+        self.features.load(assets, bytes, asset_path);
+
+        Ok(())
+    }
+}
+
+impl App {
+    // seconds the camera takes to fly to a search result
+    const FLIGHT_DURATION: f32 = 1.2;
+
+    // brings everything that depends on the layers up to date after one was added or replaced
+    fn refresh_layers(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let Self {
+            features,
+            markers,
+            feature_labels, 
+            feature_info,
+            legend,
+            selected,
+            screen_rays,
+            screen_resolution,
+            camera,
+            globe_radius, ..
+        } = self;
+
+        // the picked feature's layer may have been replaced
+        if selected.is_some_and(|pick| features.stack().entry(pick).is_none()) {
+            *selected = None;
+
+            feature_info.show(device, queue, None);
+        }
+
+        // markers follow the snapshot shown as the base layer
+        markers.set_period(queue, features.period());
+
+        let camera_uniform = camera.build_camera_uniform(*screen_resolution);
+
+        legend.update(device, queue, features.stack(), camera_uniform);
+
+        for layer in features.stack().iter() {
+            feature_labels.queue_labels_for_display(
+                &layer.geometry().metadata,
+                screen_rays,
+                camera_uniform,
+                *globe_radius,
+            );
+        }
+
+        #[allow(unused_variables)]
+        if let Err(e) = feature_labels.prepare(device, queue, *screen_resolution) {
+            // clear screen rays to prevent rendering broken labels
+            screen_rays.clear();
+
+            #[cfg(feature = "logging")] 
+            backend::log::debug!("Failed to position feature labels.\n{e}");
+        }
+    }
+
+    // flies to a feature and selects it
+    fn focus(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        camera: &mut camera::Camera,
+        stack: &layers::LayerStack,
+        feature_info: &mut feature_info::FeatureInfo,
+        selected: &mut Option<layers::Pick>,
+        pick: layers::Pick,
+    ) {
+        if let Some((centroid, extent)) = stack.frame(pick) {
+            let [lat, lon] = util::vertex_to_lat_lon(centroid);
+
+            let distance = camera.framing_distance(extent);
+
+            camera.fly_to(lat, lon, distance, Self::FLIGHT_DURATION);
+        }
+
+        *selected = Some(pick);
+
+        feature_info.show(device, queue, stack.entry(pick));
+    }
+
+    // recomputes the camera uniform and repositions labels
+    fn refresh_view(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let Self {
+            camera,
+            camera_buffer,
+            globe_radius,
+            features, 
+            feature_labels,
+            legend,
+            screen_ray_density, 
+            screen_rays,
+            screen_resolution, ..
+        } = self;
+
+        let camera_uniform = camera.build_camera_uniform(*screen_resolution);
 
         // generate rays if its okay to prepare labels
         match (camera.movement_in_progress(), screen_rays.len()) {
@@ -405,12 +857,16 @@ impl backend::App for App {
         }
 
         if !camera.movement_in_progress() {
-            feature_labels.queue_labels_for_display(
-                &feature_geometry.metadata,
-                screen_rays,
-                camera_uniform,
-                *globe_radius,
-            );
+            legend.update(device, queue, features.stack(), camera_uniform);
+
+            for layer in features.stack().iter() {
+                feature_labels.queue_labels_for_display(
+                    &layer.geometry().metadata,
+                    screen_rays,
+                    camera_uniform,
+                    *globe_radius,
+                );
+            }
 
             #[allow(unused_variables)]
             if let Err(e) = feature_labels.prepare(device, queue, *screen_resolution) {
@@ -428,62 +884,76 @@ impl backend::App for App {
             bytemuck::cast_slice(&[camera_uniform]),
         );
 
-        true
     }
-    
-    fn update(
-        &mut self, 
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        bytes: &[u8],
-        #[allow(unused_variables)]
-        asset_path: &str,
-    ) -> Result<(), Self::UpdateError> {
+
+    // adds a route through waypoints given as [lat, lon], see routes::Routes
+    // routes are drawn once they're toggled on with `R`
+    pub fn add_route(&mut self, device: &wgpu::Device, waypoints: &[[f32; 2]], color: [u8; 3]) {
+        self.routes.add_route(device, waypoints, color);
+    }
+
+    // finds the feature under a cursor given in physical pixels
+    pub fn pick(&self, cursor: backend::Position) -> Option<layers::Pick> {
         let Self {
-            feature_geometry,
-            feature_labels, 
-            screen_rays,
-            screen_resolution,
             camera,
-            globe_radius, ..
+            features,
+            globe_radius,
+            screen_resolution, ..
         } = self;
 
-        match self.features.load(device, queue, bytes, *screen_resolution) {
-            Ok(repl) => {
-                mem::replace(feature_geometry, repl).destroy();
+        Self::pick_feature(camera, features.stack(), *screen_resolution, *globe_radius, cursor)
+    }
 
-                feature_labels.queue_labels_for_display(
-                    &feature_geometry.metadata,
-                    screen_rays,
-                    camera.build_camera_uniform(*screen_resolution),
-                    *globe_radius,
-                );
+    fn pick_feature(
+        camera: &camera::Camera,
+        stack: &layers::LayerStack,
+        screen_resolution: backend::Size,
+        globe_radius: f32,
+        cursor: backend::Position,
+    ) -> Option<layers::Pick> {
+        let (camera_uniform, ray) = Self::cursor_ray(camera, screen_resolution, cursor);
 
-                #[allow(unused_variables)]
-                if let Err(e) = feature_labels.prepare(device, queue, *screen_resolution) {
-                    // clear screen rays to prevent rendering broken labels
-                    screen_rays.clear();
-    
-                    #[cfg(feature = "logging")] 
-                    backend::log::debug!("Failed to position feature labels.\n{e}");
-                }
-            },
-            #[allow(unused_variables)]
-            Err(e) => {
-                #[cfg(feature = "logging")] 
-                backend::log::debug!("Failed to parse feature [{asset_path}].\n{e}");
-            },
-        }
+        // the level being drawn is tested, which is far cheaper once zoomed out
+        let level = geom::lod_level(camera.degrees_per_pixel(screen_resolution));
 
-        Ok(())
+        stack.pick(camera_uniform, ray, globe_radius, level)
+    }
+
+    fn pick_marker(
+        camera: &camera::Camera,
+        markers: &markers::Markers,
+        screen_resolution: backend::Size,
+        cursor: backend::Position,
+    ) -> Option<usize> {
+        let (camera_uniform, ray) = Self::cursor_ray(camera, screen_resolution, cursor);
+
+        markers.pick(camera_uniform, ray)
+    }
+
+    // the ray cast from the eye through a cursor given in physical pixels
+    fn cursor_ray(
+        camera: &camera::Camera,
+        screen_resolution: backend::Size,
+        cursor: backend::Position,
+    ) -> (camera::CameraUniform, [f32; 3]) {
+        let camera_uniform = camera.build_camera_uniform(screen_resolution);
+
+        let camera::CameraUniform { view, proj, .. } = camera_uniform;
+
+        let ray = util::cursor_to_world_ray(
+            view, 
+            proj, 
+            util::cursor_to_ndc(cursor, screen_resolution),
+        );
+
+        (camera_uniform, ray)
     }
-}
 
-impl App {
     fn submit_globe_pass(
         &self,
         encoder: &mut wgpu::CommandEncoder,
         surface: &wgpu::TextureView,
+        depth: &depth::DepthTexture,
     ) {
         let Self {
             texture_bind_group,
@@ -508,7 +978,7 @@ impl App {
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(color_attachment)],
-            depth_stencil_attachment: None,
+            depth_stencil_attachment: Some(depth.attachment(wgpu::LoadOp::Clear(1.))),
             ..Default::default()
         });
 
@@ -538,18 +1008,20 @@ impl App {
         &self, 
         encoder: &mut wgpu::CommandEncoder,
         surface: &wgpu::TextureView,
-    ) -> Result<(), glyphon::RenderError> {
+        depth: &depth::DepthTexture,
+    ) {
         let Self {
+            camera,
             camera_bind_group,
             features,
-            feature_geometry: geom::Geometry {
-                vertex_buffer,
-                indices,
-                index_buffer, ..
-            },
             feature_pipeline, 
-            feature_labels, 
-            screen_rays, ..
+            outline_pipeline,
+            markers,
+            marker_pipeline,
+            routes,
+            route_pipeline,
+            selected,
+            screen_resolution, ..
         } = self;
 
         let color_attachment = wgpu::RenderPassColorAttachment {
@@ -564,26 +1036,132 @@ impl App {
         let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(color_attachment)],
-            depth_stencil_attachment: None,
+            depth_stencil_attachment: Some(depth.attachment(wgpu::LoadOp::Load)),
             ..Default::default()
         });
 
         pass.set_pipeline(feature_pipeline);
 
-        // set index buffer
-        pass.set_index_buffer(
-            index_buffer.slice(..), 
-            wgpu::IndexFormat::Uint32,
-        );
-
-        // set vertex buffer
-        pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-
         // bind camera
         pass.set_bind_group(0, camera_bind_group, &[]);
 
-        // draw
-        pass.draw_indexed(0..(indices.len() as u32), 0, 0..1);
+        // simpler borders are drawn as the camera pulls away from the globe
+        let level = geom::lod_level(camera.degrees_per_pixel(*screen_resolution));
+
+        // layers are drawn bottom to top
+        for layer in features.stack().iter() {
+            let (geometry, outline) = layer.level(level);
+
+            // bind layer opacity and border style
+            pass.set_bind_group(1, &layer.bind_group, &[]);
+
+            for (pipeline, vertex_buffer, index_buffer, index_count) in [
+                (feature_pipeline, &geometry.vertex_buffer, &geometry.index_buffer, geometry.indices.len()),
+                (outline_pipeline, &outline.vertex_buffer, &outline.index_buffer, outline.indices.len()),
+            ] {
+                pass.set_pipeline(pipeline);
+
+                // set index buffer
+                pass.set_index_buffer(
+                    index_buffer.slice(..), 
+                    wgpu::IndexFormat::Uint32,
+                );
+
+                // set vertex buffer
+                pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+
+                // draw
+                pass.draw_indexed(0..(index_count as u32), 0, 0..1);
+            }
+        }
+
+        pass.set_pipeline(feature_pipeline);
+
+        // redraw the picked feature on top of every layer
+        if let Some((geometry, ranges)) = selected.and_then(|pick| features.stack().highlighted(pick, level)) {
+            let geom::Geometry { vertex_buffer, index_buffer, .. } = geometry;
+
+            pass.set_index_buffer(
+                index_buffer.slice(..), 
+                wgpu::IndexFormat::Uint32,
+            );
+
+            pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+
+            pass.set_bind_group(1, features.stack().highlight_bind_group(), &[]);
+
+            for range in ranges {
+                pass.draw_indexed(range, 0, 0..1);
+            }
+        }
+
+        if routes.visible {
+            let geom::Geometry { vertex_buffer, indices, index_buffer, .. } = &routes.geometry;
+
+            pass.set_pipeline(route_pipeline);
+
+            pass.set_index_buffer(
+                index_buffer.slice(..), 
+                wgpu::IndexFormat::Uint32,
+            );
+
+            pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+
+            // bind the animation clock and line width
+            pass.set_bind_group(1, &routes.bind_group, &[]);
+
+            pass.draw_indexed(0..(indices.len() as u32), 0, 0..1);
+        }
+
+        if markers.active() {
+            let geom::Geometry { vertex_buffer, indices, index_buffer, .. } = &markers.geometry;
+
+            pass.set_pipeline(marker_pipeline);
+
+            pass.set_index_buffer(
+                index_buffer.slice(..), 
+                wgpu::IndexFormat::Uint32,
+            );
+
+            pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+
+            // bind the active period and hovered marker
+            pass.set_bind_group(1, &markers.bind_group, &[]);
+
+            pass.draw_indexed(0..(indices.len() as u32), 0, 0..1);
+        }
+    }
+
+    // text and controls are drawn over the scene without depth testing
+    fn submit_overlay_pass(
+        &self, 
+        encoder: &mut wgpu::CommandEncoder,
+        surface: &wgpu::TextureView,
+    ) -> Result<(), glyphon::RenderError> {
+        let Self {
+            features,
+            feature_labels, 
+            feature_info,
+            search,
+            legend,
+            screen_rays, ..
+        } = self;
+
+        let color_attachment = wgpu::RenderPassColorAttachment {
+            view: surface,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Load,
+                store: wgpu::StoreOp::Store,
+            },
+        };
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(color_attachment)],
+            depth_stencil_attachment: None,
+            ..Default::default()
+        });
 
         // only render labels if screen rays are generated
         // if they aren't then the camera is being moved
@@ -591,6 +1169,12 @@ impl App {
             feature_labels.render(&mut pass)?;
         }
 
+        feature_info.render(&mut pass)?;
+
+        legend.render(&mut pass)?;
+
+        search.render(&mut pass)?;
+
         features.render(&mut pass)?;
 
         Ok(())
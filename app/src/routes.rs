@@ -0,0 +1,219 @@
+use backend::wgpu as wgpu;
+
+use super::{geom, style};
+
+use std::{str, fmt, error, mem};
+
+#[derive(Debug)]
+pub enum RouteError {
+    MissingAsset(std::io::Error),
+    InvalidEncoding(str::Utf8Error),
+    InvalidGeoJson(Box<geojson::Error>),
+    // the route at this index isn't a line or has an invalid COLOR
+    InvalidRoute(usize),
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteError::MissingAsset(err) => write!(f, "{}", err),
+            RouteError::InvalidEncoding(err) => write!(f, "{}", err),
+            RouteError::InvalidGeoJson(err) => write!(f, "{}", err),
+            RouteError::InvalidRoute(idx) => write!(f, "route {} is invalid", idx),
+        }
+    }
+}
+
+impl error::Error for RouteError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> { None }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Debug)]
+pub struct RouteUniform {
+    // seconds, wrapped to keep precision
+    pub time: f32,
+    // in physical pixels
+    pub width: f32,
+    pub padding: [f32; 2],
+}
+
+// migrations and trade routes drawn as arrows travelling along great circles
+// toggled with `R`, the arrows keep the app redrawing while they're shown
+//
+// routes are read from a collection of lines, each with an optional COLOR
+// more can be added with Routes::add_route
+pub struct Routes {
+    // kept so the geometry can be rebuilt when a route is added
+    routes: Vec<(Vec<[f32; 2]>, [u8; 3])>,
+    pub geometry: geom::Geometry<geom::RouteVertex, ()>,
+    uniform_buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+    globe_radius: f32,
+    time: f32,
+    pub visible: bool,
+}
+
+impl Routes {
+    const WIDTH: f32 = 4.;
+
+    // longest step in degrees between interpolated points
+    const STEP: f32 = 1.;
+
+    // the animation loops after this many seconds
+    const PERIOD: f32 = 600.;
+
+    const COLOR_BASIC: [u8; 3] = [255, 200, 0];
+
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        globe_radius: f32,
+    ) -> Result<Self, RouteError> {
+        use geojson::JsonValue;
+
+        let routes = str::from_utf8(bytes)
+            .map_err(RouteError::InvalidEncoding)?
+            .parse::<geojson::GeoJson>()
+            .map_err(|e| RouteError::InvalidGeoJson(Box::new(e)))?;
+
+        let geojson::FeatureCollection {
+            features, ..
+        } = geojson::FeatureCollection::try_from(routes)
+            .map_err(|e| RouteError::InvalidGeoJson(Box::new(e)))?;
+
+        let mut routes = Vec::with_capacity(features.len());
+
+        for (idx, feature) in features.into_iter().enumerate() {
+            let geojson::Feature { geometry, properties, .. } = feature;
+
+            let Some(geojson::Value::LineString(line)) = geometry.map(|geojson::Geometry { value, .. }| value) else {
+                return Err(RouteError::InvalidRoute(idx));
+            };
+
+            let color = match properties.as_ref().and_then(|properties| properties.get("COLOR")) {
+                Some(JsonValue::Null) | None => Self::COLOR_BASIC,
+                Some(color) => style::parse_color(color).map_err(|_| RouteError::InvalidRoute(idx))?,
+            };
+
+            let waypoints = line
+                .iter()
+                .map(|pt| [pt[1] as f32, pt[0] as f32])
+                .collect();
+
+            routes.push((waypoints, color));
+        }
+
+        let geometry = geom::Geometry::build_route_geometry(device, &routes, Self::STEP, globe_radius);
+
+        let bind_group_layout = device.create_bind_group_layout(&{
+            wgpu::BindGroupLayoutDescriptor {
+                label: None,
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            }
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: mem::size_of::<RouteUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group = device.create_bind_group(&{
+            wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                }],
+            }
+        });
+
+        let routes = Self {
+            routes,
+            geometry,
+            uniform_buffer,
+            bind_group_layout,
+            bind_group,
+            globe_radius,
+            time: 0.,
+            // the dash animation keeps frames coming, so routes wait to be switched on
+            visible: false,
+        };
+
+        routes.write_uniform(queue);
+
+        Ok(routes)
+    }
+
+    fn write_uniform(&self, queue: &wgpu::Queue) {
+        let uniform = RouteUniform {
+            time: self.time,
+            width: Self::WIDTH,
+            padding: [0.; 2],
+        };
+
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    // waypoints are given as [lat, lon] and joined along great circles
+    pub fn add_route(
+        &mut self,
+        device: &wgpu::Device,
+        waypoints: &[[f32; 2]],
+        color: [u8; 3],
+    ) {
+        let Self { routes, geometry, globe_radius, .. } = self;
+
+        routes.push((waypoints.to_vec(), color));
+
+        let temp = geom::Geometry::build_route_geometry(device, routes, Self::STEP, *globe_radius);
+
+        mem::replace(geometry, temp).destroy();
+    }
+
+    pub fn handle_event(&mut self, event: backend::AppEvent) -> bool {
+        use backend::event::{KeyCode, ElementState};
+
+        match event {
+            backend::AppEvent::Key {
+                code: KeyCode::KeyR,
+                state: ElementState::Released,
+            } => {
+                self.visible = !self.visible;
+
+                true
+            },
+            _ => false,
+        }
+    }
+
+    // advances the arrows, returns true while they need redrawing
+    pub fn tick(&mut self, queue: &wgpu::Queue, dt: f32) -> bool {
+        if !self.visible || self.geometry.indices.is_empty() { return false; }
+
+        self.time = (self.time + dt) % Self::PERIOD;
+
+        self.write_uniform(queue);
+
+        true
+    }
+}
@@ -0,0 +1,313 @@
+use backend::wgpu as wgpu;
+
+use super::layers;
+
+struct SearchResult {
+    pick: layers::Pick,
+    text: String,
+    score: i32,
+}
+
+// scores how well the query matches the candidate as a case-insensitive subsequence
+// consecutive matches and matches at the start of words are preferred
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate: Vec<char> = candidate
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    let mut score = 0;
+
+    let mut prev: Option<usize> = None;
+
+    for ch in query.chars().flat_map(char::to_lowercase).filter(|ch| !ch.is_whitespace()) {
+        let start = prev.map_or(0, |prev| prev + 1);
+
+        let idx = start + candidate[start..].iter().position(|temp| *temp == ch)?;
+
+        if idx == start && prev.is_some() { score += 3; }
+
+        if idx == 0 || !candidate[idx - 1].is_alphanumeric() { score += 2; }
+
+        score -= (idx - start).min(5) as i32;
+
+        prev = Some(idx);
+    }
+
+    prev.map(|_| score)
+}
+
+// a text box for finding features by name
+// opened with `/`, closed by choosing a result or erasing an empty query
+pub struct Search {
+    font_system: glyphon::FontSystem,
+    font_attrs: glyphon::Attrs<'static>,
+    swash_cache: glyphon::SwashCache,
+    atlas: glyphon::TextAtlas,
+    buffer: glyphon::Buffer,
+    renderer: glyphon::TextRenderer,
+    active: bool,
+    query: String,
+    results: Vec<SearchResult>,
+    results_idx: usize,
+    choice: Option<layers::Pick>,
+    screen_resolution: backend::Size,
+}
+
+impl Search {
+    const METRICS: glyphon::Metrics = glyphon::Metrics::new(22., 26.);
+
+    const WIDTH: f32 = 400.;
+    const MARGIN: f32 = 16.;
+
+    const RESULTS_MAX: usize = 8;
+
+    // the property keys that are matched against the query
+    const KEYS: &'static [&'static str] = &["NAME", "ABBREVN"];
+
+    const COLOR_FOCUS: glyphon::Color = glyphon::Color::rgb(255, 0, 0);
+    const COLOR_BASIC: glyphon::Color = glyphon::Color::rgb(255, 255, 255);
+
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        surface_format: wgpu::TextureFormat,
+        font_bytes: std::sync::Arc<Vec<u8>>,
+        font_family: &'static str,
+    ) -> Self {
+        let mut font_system = glyphon::FontSystem::new_with_fonts({
+            use glyphon::fontdb::Source;
+
+            Some(Source::Binary(font_bytes))
+        });
+
+        let mut atlas = glyphon::TextAtlas::new(
+            device,
+            queue,
+            surface_format
+        );
+
+        let buffer = glyphon::Buffer::new(&mut font_system, Self::METRICS);
+
+        let renderer = glyphon::TextRenderer::new(
+            &mut atlas,
+            device,
+            wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            }, None
+        );
+
+        Self {
+            font_system,
+            font_attrs: glyphon::Attrs::new().family(glyphon::Family::Name(font_family)),
+            swash_cache: glyphon::SwashCache::new(),
+            atlas,
+            buffer,
+            renderer,
+            active: false,
+            query: String::with_capacity(0),
+            results: Vec::with_capacity(0),
+            results_idx: 0,
+            choice: None,
+            screen_resolution: backend::Size::default(),
+        }
+    }
+
+    // the result chosen by the last event, if any
+    pub fn take_choice(&mut self) -> Option<layers::Pick> {
+        self.choice.take()
+    }
+
+    pub fn handle_event(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        event: backend::AppEvent,
+        stack: &layers::LayerStack,
+    ) -> bool {
+        use backend::event::{KeyCode, ElementState};
+
+        match event {
+            backend::AppEvent::Resized(size) => {
+                self.screen_resolution = size;
+
+                self.refresh(device, queue);
+
+                // other components need to process size changes
+                false
+            },
+            backend::AppEvent::Key {
+                code: KeyCode::Slash,
+                state: ElementState::Released,
+            } if !self.active => {
+                self.active = true;
+                self.query.clear();
+                self.search(stack);
+                self.refresh(device, queue);
+
+                true
+            },
+            backend::AppEvent::Character(ch) if self.active => {
+                if !ch.is_control() {
+                    self.query.push(ch);
+                    self.search(stack);
+                    self.refresh(device, queue);
+                }
+
+                true
+            },
+            backend::AppEvent::Key { code, state: ElementState::Pressed } if self.active => {
+                match code {
+                    KeyCode::Backspace => match self.query.pop() {
+                        Some(_) => self.search(stack),
+                        None => self.active = false,
+                    },
+                    KeyCode::Escape => self.active = false,
+                    KeyCode::ArrowUp => {
+                        self.results_idx = self.results_idx.saturating_sub(1);
+                    },
+                    KeyCode::ArrowDown => {
+                        let results_max = self.results.len().saturating_sub(1);
+
+                        self.results_idx = (self.results_idx + 1).min(results_max);
+                    },
+                    KeyCode::Enter | KeyCode::NumpadEnter => {
+                        self.choice = self.results
+                            .get(self.results_idx)
+                            .map(|SearchResult { pick, .. }| *pick);
+
+                        self.active = false;
+                    },
+                    _ => { /*  */ },
+                }
+
+                self.refresh(device, queue);
+
+                true
+            },
+            // swallow remaining keys so they don't reach other components
+            backend::AppEvent::Key { .. } => self.active,
+            _ => false,
+        }
+    }
+
+    fn search(&mut self, stack: &layers::LayerStack) {
+        let Self { query, results, results_idx, .. } = self;
+
+        results.clear();
+
+        *results_idx = 0;
+
+        if query.trim().is_empty() { return; }
+
+        for layer in stack.iter() {
+            for (entry, metadata) in layer.geometry().metadata.entries.iter().enumerate() {
+                let best = Self::KEYS
+                    .iter()
+                    .filter_map(|key| match metadata.get(*key) {
+                        Some(geojson::JsonValue::String(text)) => //
+                            fuzzy_score(query, text).map(|score| (score, text)),
+                        _ => None,
+                    }).max_by_key(|(score, _)| *score);
+
+                let Some((score, text)) = best else { continue; };
+
+                // the same polity may be visible on several layers
+                if results.iter().any(|result| result.text == *text) { continue; }
+
+                results.push(SearchResult {
+                    pick: layers::Pick { layer: layer.idx, entry },
+                    text: text.to_owned(),
+                    score,
+                });
+            }
+        }
+
+        // shorter names win ties
+        results.sort_by_key(|SearchResult { score, text, .. }| (-*score, text.len()));
+        results.truncate(Self::RESULTS_MAX);
+    }
+
+    fn refresh(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        #[allow(unused_variables)]
+        if let Err(e) = self.prepare(device, queue) {
+            self.atlas.trim();
+
+            #[cfg(feature = "logging")]
+            backend::log::debug!("Failed to prepare search box.\n{e}");
+        }
+    }
+
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<(), glyphon::PrepareError> {
+        let Self {
+            font_system,
+            font_attrs,
+            swash_cache,
+            atlas,
+            buffer,
+            renderer,
+            active,
+            query,
+            results,
+            results_idx,
+            screen_resolution: backend::Size { width, height }, ..
+        } = self;
+
+        if !*active { return Ok(()); }
+
+        let prompt = format!("Search: {query}_");
+
+        let spans = Some((prompt.as_str(), font_attrs.color(Self::COLOR_BASIC)))
+            .into_iter()
+            .chain(results.iter().enumerate().flat_map(|(idx, SearchResult { text, .. })| {
+                let color = if idx == *results_idx {
+                    Self::COLOR_FOCUS
+                } else {
+                    Self::COLOR_BASIC
+                }; [("\n", font_attrs.color(Self::COLOR_BASIC)), (text.as_str(), font_attrs.color(color))]
+            }));
+
+        buffer.set_rich_text(font_system, spans, glyphon::Shaping::Basic);
+        buffer.set_size(font_system, Self::WIDTH, *height as f32);
+        buffer.shape_until_scroll(font_system);
+
+        let region = glyphon::TextArea {
+            buffer,
+            left: ((*width as f32 - Self::WIDTH) * 0.5).max(0.),
+            top: Self::MARGIN,
+            scale: 1.,
+            bounds: glyphon::TextBounds {
+                left: 0,
+                top: 0,
+                right: *width as i32,
+                bottom: *height as i32,
+            },
+            default_color: Self::COLOR_BASIC,
+        };
+
+        renderer.prepare(
+            device,
+            queue,
+            font_system,
+            atlas,
+            glyphon::Resolution { width: *width, height: *height },
+            Some(region),
+            swash_cache,
+        )
+    }
+
+    pub fn render<'p, 'a: 'p>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'p>,
+    ) -> Result<(), glyphon::RenderError> {
+        let Self { atlas, renderer, active, .. } = self;
+
+        if *active { renderer.render(atlas, pass) } else { Ok(()) }
+    }
+}
@@ -0,0 +1,225 @@
+use super::util;
+
+use std::{collections, fmt, error};
+
+#[derive(Debug)]
+pub enum StyleError {
+    MissingAsset(std::io::Error),
+    InvalidJson(String),
+    InvalidRule(usize),
+    InvalidColor(String),
+}
+
+impl fmt::Display for StyleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StyleError::MissingAsset(err) => write!(f, "{}", err),
+            StyleError::InvalidJson(err) => write!(f, "{}", err),
+            StyleError::InvalidRule(idx) => write!(f, "style rule {} is missing a key", idx),
+            StyleError::InvalidColor(color) => write!(f, "invalid color [{}]", color),
+        }
+    }
+}
+
+impl error::Error for StyleError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> { None }
+}
+
+// picks a fill color from the value of a single property
+struct Rule {
+    key: String,
+    // explicit colors for specific values
+    palette: collections::HashMap<String, [u8; 3]>,
+    // values missing from the palette are hashed when set
+    // otherwise the next rule is tried
+    hashed: bool,
+}
+
+// an ordered list of rules, the first one that matches a feature colors it
+// features that match no rule are colored by hashing their identity property
+//
+// {
+//     "rules": [
+//         { "key": "PARTOF", "palette": { "Roman Empire": "#b03a2e" }, "hashed": false },
+//         { "key": "SUBJECTO" }
+//     ],
+//     "identity": "NAME",
+//     "fallback": "#9e9e9e"
+// }
+pub struct Style {
+    rules: Vec<Rule>,
+    identity: String,
+    fallback: [u8; 3],
+}
+
+// neighbours whose colors are closer than this (euclidean, in rgb8) are told apart
+const CONTRAST_MIN: u32 = 48;
+
+// number of hashed alternatives tried before falling back to CONTRAST_PALETTE
+const CONTRAST_ATTEMPTS: u32 = 8;
+
+// colors far enough apart from each other to tell neighbours apart when hashing fails
+const CONTRAST_PALETTE: [[u8; 3]; 12] = [
+    [230, 25, 75], [60, 180, 75], [255, 225, 25], [0, 130, 200],
+    [245, 130, 48], [145, 30, 180], [70, 240, 240], [240, 50, 230],
+    [210, 245, 60], [250, 190, 212], [0, 128, 128], [170, 110, 40],
+];
+
+impl Style {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StyleError> {
+        use geojson::JsonValue;
+
+        let style = std::str::from_utf8(bytes)
+            .map_err(|e| StyleError::InvalidJson(e.to_string()))?
+            .parse::<JsonValue>()
+            .map_err(|e| StyleError::InvalidJson(e.to_string()))?;
+
+        let mut rules = Vec::with_capacity(0);
+
+        if let Some(JsonValue::Array(temp)) = style.get("rules") {
+            for (idx, rule) in temp.iter().enumerate() {
+                let Some(JsonValue::String(key)) = rule.get("key") else {
+                    return Err(StyleError::InvalidRule(idx));
+                };
+
+                let mut palette = collections::HashMap::new();
+
+                if let Some(JsonValue::Object(temp)) = rule.get("palette") {
+                    for (value, color) in temp.iter() {
+                        palette.insert(value.to_owned(), parse_color(color)?);
+                    }
+                }
+
+                let hashed = !matches!(rule.get("hashed"), Some(JsonValue::Bool(false)));
+
+                rules.push(Rule { key: key.to_owned(), palette, hashed });
+            }
+        }
+
+        let identity = match style.get("identity") {
+            Some(JsonValue::String(identity)) => identity.to_owned(),
+            _ => String::from("NAME"),
+        };
+
+        let fallback = match style.get("fallback") {
+            Some(color) => parse_color(color)?,
+            None => [158, 158, 158],
+        };
+
+        Ok(Self { rules, identity, fallback })
+    }
+
+    // the fill color along with the property value that chose it
+    // features sharing that value are meant to share a color
+    pub fn fill<'a>(&self, metadata: &'a geojson::JsonObject) -> ([u8; 3], Option<&'a str>) {
+        fn value<'a>(metadata: &'a geojson::JsonObject, key: &str) -> Option<&'a str> {
+            match metadata.get(key) {
+                Some(geojson::JsonValue::String(value)) if !value.is_empty() => Some(value),
+                _ => None,
+            }
+        }
+
+        for Rule { key, palette, hashed } in self.rules.iter() {
+            let Some(value) = value(metadata, key) else { continue; };
+
+            match palette.get(value) {
+                Some(color) => return (*color, Some(value)),
+                None if *hashed => return (util::str_to_rgb8(value), Some(value)),
+                None => { /*  */ },
+            }
+        }
+
+        match value(metadata, &self.identity) {
+            Some(value) => (util::str_to_rgb8(value), Some(value)),
+            None => (self.fallback, None),
+        }
+    }
+}
+
+// recolors features whose color can't be told apart from an adjacent feature's
+// features with the same fill key are recolored together from their combined borders
+// so they keep looking alike, keys are visited in order of first appearance
+//
+// hashed alternatives are tried first, then CONTRAST_PALETTE
+// a key bordering more colors than the palette can avoid gets the palette color
+// furthest from its neighbours, so a clash remains possible but is as faint as it can be
+pub fn separate_adjacent(
+    colors: &mut [[u8; 3]],
+    keys: &[Option<String>],
+    adjacency: &[Vec<usize>],
+) {
+    fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+        a.iter()
+            .zip(b.iter())
+            .map(|(a, b)| (*a as i32 - *b as i32).pow(2) as u32)
+            .sum::<u32>()
+    }
+
+    // features without a key stand alone
+    let mut groups: Vec<Vec<usize>> = Vec::with_capacity(0);
+    let mut group_of = Vec::with_capacity(colors.len());
+
+    let mut by_key = collections::HashMap::new();
+
+    for (idx, key) in keys.iter().enumerate() {
+        let group = match key.as_deref() {
+            Some(key) => *by_key.entry(key).or_insert_with(|| {
+                groups.push(Vec::with_capacity(0)); groups.len() - 1
+            }),
+            None => { groups.push(Vec::with_capacity(0)); groups.len() - 1 },
+        };
+
+        groups[group].push(idx);
+        group_of.push(group);
+    }
+
+    for (group, members) in groups.iter().enumerate() {
+        // colors of the groups before this one that border any of its members
+        let neighbours = members
+            .iter()
+            .flat_map(|idx| adjacency[*idx].iter())
+            .map(|idx| group_of[*idx])
+            .filter(|temp| *temp < group)
+            .map(|temp| colors[groups[temp][0]])
+            .collect::<Vec<_>>();
+
+        let contrast = |color: [u8; 3]| neighbours
+            .iter()
+            .map(|temp| distance(*temp, color))
+            .min()
+            .unwrap_or(u32::MAX);
+
+        let clashes = |color: [u8; 3]| contrast(color) < CONTRAST_MIN.pow(2);
+
+        if !clashes(colors[members[0]]) { continue; }
+
+        let key = keys[members[0]].as_deref().unwrap_or_default();
+
+        let color = (1..=CONTRAST_ATTEMPTS)
+            .map(|attempt| util::str_to_rgb8(&format!("{key}#{attempt}")))
+            .chain(CONTRAST_PALETTE.iter().copied())
+            .find(|color| !clashes(*color))
+            .or_else(|| CONTRAST_PALETTE.iter().copied().max_by_key(|color| contrast(*color)))
+            .unwrap_or(colors[members[0]]);
+
+        for idx in members.iter() { colors[*idx] = color; }
+    }
+}
+
+// accepts colors in the form #rrggbb
+pub fn parse_color(color: &geojson::JsonValue) -> Result<[u8; 3], StyleError> {
+    let invalid = || StyleError::InvalidColor(color.to_string());
+
+    let hex = color
+        .as_str()
+        .and_then(|color| color.strip_prefix('#'))
+        .filter(|hex| hex.len() == 6)
+        .ok_or_else(invalid)?;
+
+    let channel = |idx: usize| u8::from_str_radix(hex.get(idx..(idx + 2))?, 16).ok();
+
+    match [channel(0), channel(2), channel(4)] {
+        [Some(r), Some(g), Some(b)] => Ok([r, g, b]),
+        _ => Err(invalid()),
+    }
+}
@@ -0,0 +1,512 @@
+use backend::wgpu as wgpu;
+
+pub fn format_year(year: i32) -> String {
+    if year < 0 {
+        format!("{} BC", -year)
+    } else {
+        format!("{year}")
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct TimelineVertex {
+    pub pos: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl TimelineVertex {
+    const VERTEX_ATTRIBUTES: &'static [wgpu::VertexAttribute] = &{
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x4]
+    };
+
+    pub fn layout() -> wgpu::VertexBufferLayout<'static> {
+        use std::mem;
+
+        wgpu::VertexBufferLayout {
+            array_stride: mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: Self::VERTEX_ATTRIBUTES,
+        }
+    }
+}
+
+pub struct Timeline {
+    catalog: &'static [backend::FeatureLayer<'static>],
+    // (year, index into the feature list), sorted by year
+    snapshots: Vec<(i32, usize)>,
+    year: f32,
+    playing: bool,
+    // seconds since playback last stepped
+    elapsed: f32,
+    dragging: bool,
+    screen_resolution: backend::Size,
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+    font_system: glyphon::FontSystem,
+    font_attrs: glyphon::Attrs<'static>,
+    swash_cache: glyphon::SwashCache,
+    atlas: glyphon::TextAtlas,
+    controls: glyphon::Buffer,
+    label: glyphon::Buffer,
+    renderer: glyphon::TextRenderer,
+}
+
+impl Timeline {
+    const METRICS: glyphon::Metrics = glyphon::Metrics::new(24., 28.);
+
+    // height of the strip along the bottom of the screen
+    pub const HEIGHT: f32 = 48.;
+
+    const MARGIN: f32 = 16.;
+    const CONTROLS_WIDTH: f32 = 72.;
+    const LABEL_WIDTH: f32 = 120.;
+
+    // seconds each snapshot is shown for during playback
+    const STEP_INTERVAL: f32 = 2.;
+
+    const COLOR_STRIP: [f32; 4] = [0., 0., 0., 0.6];
+    const COLOR_TRACK: [f32; 4] = [0.5, 0.5, 0.5, 1.];
+    const COLOR_TICK: [f32; 4] = [1., 1., 1., 1.];
+    const COLOR_HANDLE: [f32; 4] = [1., 0., 0., 1.];
+
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        config: crate::Config<'static>,
+        font_bytes: std::sync::Arc<Vec<u8>>,
+        shader: &wgpu::ShaderModule,
+    ) -> Self {
+        let mut snapshots: Vec<(i32, usize)> = config.features
+            .iter()
+            .enumerate()
+            .map(|(idx, backend::FeatureLayer { year, .. })| (*year, idx))
+            .collect();
+
+        snapshots.sort_by_key(|(year, _)| *year);
+
+        let year = snapshots
+            .iter()
+            .find(|(_, idx)| *idx == 0)
+            .map(|(year, _)| *year as f32)
+            .unwrap_or(0.);
+
+        let pipeline_layout = device.create_pipeline_layout(&{
+            wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[],
+                push_constant_ranges: &[],
+            }
+        });
+
+        let pipeline = device.create_render_pipeline(&{
+            wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: shader,
+                    entry_point: "vertex",
+                    buffers: &[TimelineVertex::layout()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: shader,
+                    entry_point: "fragment",
+                    targets: &[
+                        Some(wgpu::ColorTargetState {
+                            format: config.surface_format,
+                            blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        })
+                    ],
+                }),
+                depth_stencil: None,
+                multiview: None,
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleList,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None,
+                    unclipped_depth: false,
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    conservative: false,
+                },
+            }
+        });
+
+        // the strip, the track, the handle and one tick per snapshot
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: (std::mem::size_of::<TimelineVertex>() * 6 * (3 + snapshots.len())) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut font_system = glyphon::FontSystem::new_with_fonts({
+            use glyphon::fontdb::Source;
+
+            Some(Source::Binary(font_bytes))
+        });
+
+        let font_attrs = glyphon::Attrs::new()
+            .family(glyphon::Family::Name(config.font_family));
+
+        let mut atlas = glyphon::TextAtlas::new(
+            device,
+            queue,
+            config.surface_format
+        );
+
+        let controls = glyphon::Buffer::new(&mut font_system, Self::METRICS);
+
+        let label = glyphon::Buffer::new(&mut font_system, Self::METRICS);
+
+        let renderer = glyphon::TextRenderer::new(
+            &mut atlas,
+            device,
+            wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            }, None
+        );
+
+        Self {
+            catalog: config.features,
+            snapshots,
+            year,
+            playing: false,
+            elapsed: 0.,
+            dragging: false,
+            screen_resolution: backend::Size::default(),
+            pipeline,
+            vertex_buffer,
+            vertex_count: 0,
+            font_system,
+            font_attrs,
+            swash_cache: glyphon::SwashCache::new(),
+            atlas,
+            controls,
+            label,
+            renderer,
+        }
+    }
+
+    pub fn playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+        self.elapsed = 0.;
+    }
+
+    fn toggle_playing(&mut self) {
+        self.playing = !self.playing;
+        self.elapsed = 0.;
+    }
+
+    // runs playback for `dt` seconds, returns true if the slider stepped
+    pub fn tick(&mut self, dt: f32) -> bool {
+        if !self.playing { return false; }
+
+        self.elapsed += dt;
+
+        if self.elapsed < Self::STEP_INTERVAL { return false; }
+
+        self.elapsed = 0.;
+
+        self.advance();
+
+        true
+    }
+
+    // index of the feature set whose year is closest to the slider's year
+    pub fn selection(&self) -> Option<usize> {
+        let Self { snapshots, year, .. } = self;
+
+        snapshots
+            .iter()
+            .min_by(|(a, _), (b, _)| {
+                let a = (*a as f32 - year).abs();
+                let b = (*b as f32 - year).abs();
+
+                a.total_cmp(&b)
+            }).map(|(_, idx)| *idx)
+    }
+
+    // moves the slider onto the given feature set
+    pub fn seek_to_feature(&mut self, idx: usize) {
+        if let Some((year, _)) = self.snapshots.iter().find(|(_, temp)| *temp == idx) {
+            self.year = *year as f32;
+        }
+    }
+
+    // steps playback to the snapshot following the current selection
+    // playback stops once the last snapshot has been reached
+    fn advance(&mut self) {
+        let Self { snapshots, year, playing, .. } = self;
+
+        match snapshots.iter().find(|(temp, _)| *temp as f32 > *year) {
+            Some((temp, _)) => *year = *temp as f32,
+            None => *playing = false,
+        }
+    }
+
+    fn track(&self) -> (f32, f32, f32) {
+        let backend::Size { width, height } = self.screen_resolution;
+
+        let left = Self::MARGIN + Self::CONTROLS_WIDTH;
+        let right = (width as f32 - Self::MARGIN - Self::LABEL_WIDTH).max(left + 1.);
+
+        (left, right, height as f32 - Self::HEIGHT * 0.5)
+    }
+
+    // snapshots are spaced evenly along the track
+    // the year is interpolated linearly between neighbouring snapshots
+    fn year_to_fraction(&self, year: f32) -> f32 {
+        let Self { snapshots, .. } = self;
+
+        if snapshots.len() < 2 { return 0.; }
+
+        let idx = snapshots.partition_point(|(temp, _)| *temp as f32 <= year);
+
+        if idx == 0 { return 0.; }
+        if idx == snapshots.len() { return 1.; }
+
+        let (a, _) = snapshots[idx - 1];
+        let (b, _) = snapshots[idx];
+
+        let t = (year - a as f32) / (b - a) as f32;
+
+        ((idx - 1) as f32 + t) / (snapshots.len() - 1) as f32
+    }
+
+    fn fraction_to_year(&self, t: f32) -> f32 {
+        let Self { snapshots, .. } = self;
+
+        match snapshots.len() {
+            0 => 0.,
+            1 => snapshots[0].0 as f32,
+            len => {
+                let t = t.clamp(0., 1.) * (len - 1) as f32;
+
+                let idx = (t.floor() as usize).min(len - 2);
+
+                let (a, _) = snapshots[idx];
+                let (b, _) = snapshots[idx + 1];
+
+                a as f32 + (b - a) as f32 * (t - idx as f32)
+            },
+        }
+    }
+
+    fn seek(&mut self, x: f32) {
+        let (left, right, _) = self.track();
+
+        self.year = self.fraction_to_year((x - left) / (right - left));
+    }
+
+    pub fn handle_event(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        event: backend::AppEvent,
+    ) -> bool {
+        let handled = match event {
+            backend::AppEvent::Resized(size) => {
+                self.screen_resolution = size;
+
+                #[allow(unused_variables)]
+                if let Err(e) = self.prepare(device, queue) {
+                    self.atlas.trim();
+
+                    #[cfg(feature = "logging")]
+                    backend::log::debug!("Failed to prepare timeline.\n{e}");
+                }
+
+                // other components need to process size changes
+                return false;
+            },
+            backend::AppEvent::Mouse {
+                button: backend::event::MouseButton::Left,
+                state: backend::event::ElementState::Pressed,
+                cursor: backend::Position { x, y },
+            } if y > self.screen_resolution.height as f32 - Self::HEIGHT => {
+                let (left, right, _) = self.track();
+
+                if x < left {
+                    self.toggle_playing();
+                } else if x <= right + Self::MARGIN {
+                    self.playing = false;
+                    self.dragging = true;
+                    self.seek(x);
+                }
+
+                true
+            },
+            backend::AppEvent::CursorMoved { 
+                cursor: backend::Position { x, .. },
+            } if self.dragging => {
+                self.seek(x);
+
+                true
+            },
+            backend::AppEvent::Mouse {
+                button: backend::event::MouseButton::Left,
+                state: backend::event::ElementState::Released,
+                cursor: backend::Position { x, .. },
+            } if self.dragging => {
+                self.dragging = false;
+                self.seek(x);
+
+                true
+            },
+            backend::AppEvent::Key {
+                code: backend::event::KeyCode::Space,
+                state: backend::event::ElementState::Released,
+            } => { self.toggle_playing(); true },
+            _ => false,
+        };
+
+        if handled { self.refresh(device, queue); }
+
+        handled
+    }
+
+    pub fn refresh(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        #[allow(unused_variables)]
+        if let Err(e) = self.prepare(device, queue) {
+            self.atlas.trim();
+
+            #[cfg(feature = "logging")]
+            backend::log::debug!("Failed to prepare timeline.\n{e}");
+        }
+    }
+
+    fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<(), glyphon::PrepareError> {
+        let backend::Size { width, height } = self.screen_resolution;
+
+        if width == 0 || height == 0 { return Ok(()); }
+
+        let (left, right, center) = self.track();
+
+        let handle = left + (right - left) * self.year_to_fraction(self.year);
+
+        let mut vertices = Vec::with_capacity(6 * (3 + self.snapshots.len()));
+
+        let mut quad = |x0: f32, y0: f32, x1: f32, y1: f32, color: [f32; 4]| {
+            let x0 = x0 / width as f32 * 2. - 1.;
+            let x1 = x1 / width as f32 * 2. - 1.;
+            let y0 = 1. - y0 / height as f32 * 2.;
+            let y1 = 1. - y1 / height as f32 * 2.;
+
+            vertices.extend([
+                [x0, y0], [x1, y0], [x1, y1],
+                [x0, y0], [x1, y1], [x0, y1],
+            ].map(|pos| TimelineVertex { pos, color }));
+        };
+
+        quad(0., height as f32 - Self::HEIGHT, width as f32, height as f32, Self::COLOR_STRIP);
+        quad(left, center - 2., right, center + 2., Self::COLOR_TRACK);
+
+        for (year, _) in self.snapshots.iter() {
+            let x = left + (right - left) * self.year_to_fraction(*year as f32);
+
+            quad(x - 1., center - 6., x + 1., center + 6., Self::COLOR_TICK);
+        }
+
+        quad(handle - 5., center - 10., handle + 5., center + 10., Self::COLOR_HANDLE);
+
+        self.vertex_count = vertices.len() as u32;
+
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+
+        // the label names the snapshot the slider has landed on
+        let label_text = self.selection().map_or("", |idx| self.catalog[idx].label);
+
+        let Self {
+            playing,
+            font_system,
+            font_attrs,
+            swash_cache,
+            atlas,
+            controls,
+            label,
+            renderer, ..
+        } = self;
+
+        let controls_text = if *playing { "Pause" } else { "Play" };
+
+        #[allow(unused_parens)]
+        controls.set_text(font_system, controls_text, (*font_attrs), glyphon::Shaping::Basic);
+        controls.set_size(font_system, Self::CONTROLS_WIDTH, Self::HEIGHT);
+        controls.shape_until_scroll(font_system);
+
+        #[allow(unused_parens)]
+        label.set_text(font_system, label_text, (*font_attrs), glyphon::Shaping::Basic);
+        label.set_size(font_system, Self::LABEL_WIDTH, Self::HEIGHT);
+        label.shape_until_scroll(font_system);
+
+        let top = center - Self::METRICS.line_height * 0.5;
+
+        let area = |buffer, left: f32| glyphon::TextArea {
+            buffer,
+            left,
+            top,
+            scale: 1.,
+            bounds: glyphon::TextBounds {
+                left: 0,
+                top: 0,
+                right: width as i32,
+                bottom: height as i32,
+            },
+            default_color: glyphon::Color::rgb(255, 255, 255),
+        };
+
+        let regions = [
+            area(controls, Self::MARGIN),
+            area(label, right + Self::MARGIN),
+        ];
+
+        renderer.prepare(
+            device,
+            queue,
+            font_system,
+            atlas,
+            glyphon::Resolution { width, height },
+            regions,
+            swash_cache,
+        )
+    }
+
+    pub fn render<'p, 'a: 'p>(
+        &'a self,
+        pass: &mut wgpu::RenderPass<'p>,
+    ) -> Result<(), glyphon::RenderError> {
+        let Self {
+            pipeline,
+            vertex_buffer,
+            vertex_count,
+            atlas,
+            renderer, ..
+        } = self;
+
+        pass.set_pipeline(pipeline);
+
+        pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+
+        pass.draw(0..*vertex_count, 0..1);
+
+        renderer.render(atlas, pass)
+    }
+}
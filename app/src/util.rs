@@ -1,6 +1,6 @@
 use backend::wgpu as wgpu;
 
-use std::{hash, io};
+use std::io;
 
 type ShaderResult<'a> = io::Result<wgpu::ShaderModuleDescriptor<'a>>;
 
@@ -59,6 +59,20 @@ pub fn cursor_to_world_ray(
         .xyz().normalized().as_array()
 }
 
+// converts a cursor position in physical pixels to normalized device coordinates
+pub fn cursor_to_ndc(
+    cursor: backend::Position,
+    screen_resolution: backend::Size,
+) -> backend::Position {
+    let backend::Position { x, y } = cursor;
+    let backend::Size { width, height } = screen_resolution;
+
+    backend::Position {
+        x: x / width.max(1) as f32 * 2. - 1.,
+        y: 1. - y / height.max(1) as f32 * 2.,
+    }
+}
+
 pub fn world_to_screen_space(
     vertex: [f32; 3],
     view: [[f32; 4]; 4],
@@ -112,7 +126,7 @@ pub fn intrs(
         f32::MAX
     } else if det > EPS && (v < 0. || u + v > det) {
         f32::MAX
-    } else if det < EPS * -1. {
+    } else if det < -EPS {
         f32::MAX
     } else {
         let w = e2.dot(q) / det;
@@ -124,6 +138,22 @@ pub fn intrs(
     }
 }
 
+// cheap rejection test before intersecting a ray with individual triangles
+pub fn intrs_sphere(
+    eye: [f32; 4],
+    ray: [f32; 3],
+    center: [f32; 3],
+    radius: f32,
+) -> bool {
+    let eye = ultraviolet::Vec4::from(eye).xyz();
+
+    let oc = ultraviolet::Vec3::from(center) - eye;
+
+    let t = oc.dot(ultraviolet::Vec3::from(ray));
+
+    t > -radius && oc.mag_sq() - t * t <= radius * radius
+}
+
 pub fn hemisphere_maxima_sq(
     eye: [f32; 4],
     globe_radius: f32,
@@ -141,21 +171,46 @@ pub fn lat_lon_to_vertex(pt: [f32; 2], globe_radius: f32) -> [f32; 3] {
     let lon = pt[1].to_radians();
 
     [
-        lat.cos() * lon.cos() * globe_radius * -1., 
+        -lat.cos() * lon.cos() * globe_radius, 
         lat.sin() * globe_radius,
         lat.cos() * lon.sin() * globe_radius,
     ]
 }
 
-#[allow(unused_parens, clippy::double_parens)]
-pub fn hashable_to_rgb8(name: &(impl hash::Hash)) -> [u8; 3] {
-    use hash::Hasher as _;
+// spherical interpolation between two unit vectors separated by `angle` radians
+// antipodal vectors have no single arc between them, callers split those beforehand
+pub fn slerp(
+    a: ultraviolet::Vec3, 
+    b: ultraviolet::Vec3, 
+    angle: f32, 
+    t: f32,
+) -> ultraviolet::Vec3 {
+    // nearly parallel vectors are interpolated linearly
+    if angle.sin().abs() < f32::EPSILON {
+        return (a + (b - a) * t).normalized();
+    }
 
-    let mut hasher = hash::DefaultHasher::new();
+    (a * ((1. - t) * angle).sin() + b * (t * angle).sin()) / angle.sin()
+}
 
-    name.hash(&mut hasher);
+// inverse of lat_lon_to_vertex
+pub fn vertex_to_lat_lon(vertex: [f32; 3]) -> [f32; 2] {
+    let [x, y, z] = *ultraviolet::Vec3::from(vertex).normalized().as_array();
 
-    let color_hash = hasher.finish();
+    [(-y).asin().to_degrees(), (-z).atan2(x).to_degrees()]
+}
+
+// 64-bit FNV-1a, unlike DefaultHasher its output never changes between builds
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    bytes.iter().fold(OFFSET, |hash, byte| (hash ^ *byte as u64).wrapping_mul(PRIME))
+}
+
+#[allow(unused_parens, clippy::double_parens)]
+pub fn str_to_rgb8(name: &str) -> [u8; 3] {
+    let color_hash = fnv1a(name.as_bytes());
     let color = [
         ((color_hash & 0xFF0000) >> 16) as u8,
         ((color_hash & 0x00FF00) >> 8) as u8,
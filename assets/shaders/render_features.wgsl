@@ -3,11 +3,26 @@
 @group(0) @binding(0)
 var<uniform> camera: CameraUniform;
 
+struct LayerUniform {
+    border_color: vec4<f32>,
+    opacity: f32,
+    highlight: f32,
+    hovered: u32,
+    border_width: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> layer: LayerUniform;
+
 struct VertexInput {
     @location(0) 
     pos: vec3<f32>,
     @location(1)
     color: vec3<f32>,
+    @location(2)
+    feature: u32,
+    @location(3)
+    border_precision: u32,
 };
 
 struct VertexOutput {
@@ -15,18 +30,47 @@ struct VertexOutput {
     pos_clip: vec4<f32>,
     @location(0)
     color: vec3<f32>,
+    @location(1) @interpolate(flat)
+    feature: u32,
+    @location(2) @interpolate(flat)
+    border_precision: u32,
 };
 
+// fraction of the radius by which features are pulled towards the eye
+// keeps them above the globe where its facets bulge past flat polygons
+const DEPTH_BIAS: f32 = 0.001;
+
 @vertex
 fn vertex(model: VertexInput) -> VertexOutput {
+    // moving along the view ray changes depth but not screen position
+    let pos = model.pos + normalize(camera.eye.xyz - model.pos) * length(model.pos) * DEPTH_BIAS;
+
     var out: VertexOutput;
-        out.pos_clip = camera.proj * camera.view * vec4<f32>(model.pos, 1.0);
+        out.pos_clip = camera.proj * camera.view * vec4<f32>(pos, 1.0);
         out.color = model.color;
+        out.feature = model.feature;
+        out.border_precision = model.border_precision;
 
     return out;
 }
 
+const HOVER_SHADE: f32 = 0.8;
+
+// fills of features with approximate or moderately precise borders are drawn more transparent
+const ALPHA_APPROXIMATE: f32 = 0.6;
+const ALPHA_MODERATE: f32 = 0.8;
+
+fn precision_alpha(border_precision: u32) -> f32 {
+    return select(select(ALPHA_APPROXIMATE, ALPHA_MODERATE, border_precision == 2u), 1.0, border_precision >= 3u);
+}
+
 @fragment
 fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.color * 0.5, 0.75);
+    var shade = mix(0.5, 1.0, layer.highlight);
+
+    if (in.feature == layer.hovered) {
+        shade = max(shade, HOVER_SHADE);
+    }
+
+    return vec4<f32>(in.color * shade, layer.opacity * precision_alpha(in.border_precision));
 }
\ No newline at end of file
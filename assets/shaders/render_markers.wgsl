@@ -0,0 +1,84 @@
+//include shaders/types/camera.wgsl
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct MarkerUniform {
+    period: vec2<i32>,
+    hovered: u32,
+    size: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> markers: MarkerUniform;
+
+struct VertexInput {
+    @location(0)
+    center: vec3<f32>,
+    @location(1)
+    corner: vec2<f32>,
+    @location(2)
+    color: vec3<f32>,
+    @location(3)
+    marker: u32,
+    @location(4)
+    years: vec2<i32>,
+};
+
+struct VertexOutput {
+    @builtin(position)
+    pos_clip: vec4<f32>,
+    @location(0)
+    corner: vec2<f32>,
+    @location(1)
+    color: vec3<f32>,
+    @location(2) @interpolate(flat)
+    hovered: u32,
+};
+
+// markers sit above the outlines of the features beneath them
+const DEPTH_BIAS: f32 = 0.002;
+
+@vertex
+fn vertex(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    // markers outside the active period are pushed past the far plane
+    if (model.years.x >= markers.period.y || model.years.y < markers.period.x) {
+        out.pos_clip = vec4<f32>(0.0, 0.0, 2.0, 1.0);
+
+        return out;
+    }
+
+    let pos = model.center + normalize(camera.eye.xyz - model.center) * length(model.center) * DEPTH_BIAS;
+
+    let center = camera.proj * camera.view * vec4<f32>(pos, 1.0);
+
+        out.pos_clip = center + vec4<f32>(model.corner * markers.size / camera.viewport.xy * center.w, 0.0, 0.0);
+        out.corner = model.corner;
+        out.color = model.color;
+        out.hovered = select(0u, 1u, model.marker == markers.hovered);
+
+    return out;
+}
+
+// fraction of the radius filled with the marker's color, the rest is its rim
+const RIM: f32 = 0.7;
+const RIM_COLOR: vec3<f32> = vec3<f32>(0.1, 0.1, 0.1);
+
+const HOVER_SHADE: f32 = 1.3;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let r = length(in.corner);
+
+    if (r > 1.0) {
+        discard;
+    }
+
+    let shade = select(1.0, HOVER_SHADE, in.hovered == 1u);
+
+    let color = select(min(in.color * shade, vec3<f32>(1.0)), RIM_COLOR, r > RIM);
+
+    return vec4<f32>(color, 1.0);
+}
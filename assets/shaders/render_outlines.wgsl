@@ -0,0 +1,85 @@
+//include shaders/types/camera.wgsl
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct LayerUniform {
+    border_color: vec4<f32>,
+    opacity: f32,
+    highlight: f32,
+    hovered: u32,
+    border_width: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> layer: LayerUniform;
+
+struct VertexInput {
+    @location(0)
+    start: vec3<f32>,
+    @location(1)
+    end: vec3<f32>,
+    @location(2)
+    corner: vec2<f32>,
+    @location(3)
+    distance: f32,
+    @location(4)
+    feature: u32,
+    @location(5)
+    border_precision: u32,
+};
+
+struct VertexOutput {
+    @builtin(position)
+    pos_clip: vec4<f32>,
+    @location(0)
+    distance: f32,
+    @location(1) @interpolate(flat)
+    border_precision: u32,
+};
+
+// outlines sit slightly above the fills they border
+const DEPTH_BIAS: f32 = 0.0015;
+
+fn project(pos: vec3<f32>) -> vec4<f32> {
+    let biased = pos + normalize(camera.eye.xyz - pos) * length(pos) * DEPTH_BIAS;
+
+    return camera.proj * camera.view * vec4<f32>(biased, 1.0);
+}
+
+@vertex
+fn vertex(model: VertexInput) -> VertexOutput {
+    let start = project(model.start);
+    let end = project(model.end);
+
+    // direction of the segment in pixels
+    // the offset keeps degenerate segments from normalizing a zero vector
+    let dir = normalize((end.xy / end.w - start.xy / start.w) * camera.viewport.xy + vec2<f32>(1e-6, 0.0));
+    let normal = vec2<f32>(-dir.y, dir.x);
+
+    var out: VertexOutput;
+        out.pos_clip = mix(start, end, model.corner.x);
+        out.pos_clip += vec4<f32>(normal * model.corner.y * layer.border_width / camera.viewport.xy * out.pos_clip.w, 0.0, 0.0);
+        out.distance = model.distance;
+        out.border_precision = model.border_precision;
+
+    return out;
+}
+
+// approximate borders are dashed, dash length is given in degrees along the ring
+const DASH_LENGTH: f32 = 0.75;
+
+// borders fade with decreasing border_precision
+const ALPHA_APPROXIMATE: f32 = 0.5;
+const ALPHA_MODERATE: f32 = 0.75;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    if (in.border_precision <= 1u && fract(in.distance / DASH_LENGTH) > 0.5) {
+        discard;
+    }
+
+    let alpha = select(select(ALPHA_APPROXIMATE, ALPHA_MODERATE, in.border_precision == 2u), 1.0, in.border_precision >= 3u);
+
+    return vec4<f32>(layer.border_color.rgb, layer.border_color.a * layer.opacity * alpha);
+}
@@ -0,0 +1,76 @@
+//include shaders/types/camera.wgsl
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct RouteUniform {
+    time: f32,
+    width: f32,
+    padding: vec2<f32>,
+};
+
+@group(1) @binding(0)
+var<uniform> route: RouteUniform;
+
+struct VertexInput {
+    @location(0)
+    start: vec3<f32>,
+    @location(1)
+    end: vec3<f32>,
+    @location(2)
+    corner: vec2<f32>,
+    @location(3)
+    distance: f32,
+    @location(4)
+    color: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position)
+    pos_clip: vec4<f32>,
+    @location(0)
+    distance: f32,
+    @location(1)
+    side: f32,
+    @location(2)
+    color: vec3<f32>,
+};
+
+@vertex
+fn vertex(model: VertexInput) -> VertexOutput {
+    let start = camera.proj * camera.view * vec4<f32>(model.start, 1.0);
+    let end = camera.proj * camera.view * vec4<f32>(model.end, 1.0);
+
+    // direction of the segment in pixels
+    // the offset keeps degenerate segments from normalizing a zero vector
+    let dir = normalize((end.xy / end.w - start.xy / start.w) * camera.viewport.xy + vec2<f32>(1e-6, 0.0));
+    let normal = vec2<f32>(-dir.y, dir.x);
+
+    var out: VertexOutput;
+        out.pos_clip = mix(start, end, model.corner.x);
+        out.pos_clip += vec4<f32>(normal * model.corner.y * route.width / camera.viewport.xy * out.pos_clip.w, 0.0, 0.0);
+        out.distance = model.distance;
+        out.side = model.corner.y;
+        out.color = model.color;
+
+    return out;
+}
+
+// arrows are spaced this many degrees apart and travel at this many degrees per second
+const ARROW_SPACING: f32 = 2.0;
+const ARROW_SPEED: f32 = 1.5;
+
+// how far the edges of an arrow trail its tip, as a fraction of the spacing
+const ARROW_SWEEP: f32 = 0.25;
+
+const ALPHA_LINE: f32 = 0.45;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // chevrons pointing in the direction of travel
+    let phase = fract((in.distance - route.time * ARROW_SPEED) / ARROW_SPACING + abs(in.side) * ARROW_SWEEP);
+
+    let alpha = select(ALPHA_LINE, 1.0, phase > 0.5);
+
+    return vec4<f32>(in.color, alpha);
+}
@@ -0,0 +1,27 @@
+struct VertexInput {
+    @location(0)
+    pos: vec2<f32>,
+    @location(1)
+    color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position)
+    pos_clip: vec4<f32>,
+    @location(0)
+    color: vec4<f32>,
+};
+
+@vertex
+fn vertex(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+        out.pos_clip = vec4<f32>(model.pos, 0.0, 1.0);
+        out.color = model.color;
+
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
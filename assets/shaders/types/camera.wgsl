@@ -2,4 +2,5 @@ struct CameraUniform {
     eye: vec4<f32>,
     view: mat4x4<f32>,
     proj: mat4x4<f32>,
+    viewport: vec4<f32>,
 };
\ No newline at end of file
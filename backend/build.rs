@@ -1,4 +1,4 @@
-use std::{fs, io, path};
+use std::{env, fs, io, path};
 
 fn build_assets(root: path::PathBuf) -> io::Result<()> {
     use static_files::resource_dir;
@@ -16,6 +16,81 @@ fn build_assets(root: path::PathBuf) -> io::Result<()> {
     resource_dir(assets_dir).build()
 }
 
+// extracts a signed year from names like `world_bc323`
+// years before the common era are negative
+fn parse_year(stem: &str) -> Option<i32> {
+    let (_, year) = stem.rsplit_once('_')?;
+
+    match year.strip_prefix("bc") {
+        Some(year) => year.parse::<i32>().ok().map(|year| -year),
+        None => year.parse::<i32>().ok(),
+    }
+}
+
+// must stay in step with timeline::format_year in the app crate
+fn format_year(year: i32) -> String {
+    if year < 0 {
+        format!("{} BC", -year)
+    } else {
+        format!("{year}")
+    }
+}
+
+fn build_feature_catalog(root: path::PathBuf) -> io::Result<()> {
+    use std::fmt::Write as _;
+
+    let features_dir = root.join("features");
+
+    let mut layers = Vec::new();
+
+    if features_dir.exists() {
+        for entry in fs::read_dir(&features_dir)? {
+            let path = entry?.path();
+
+            if path.extension().and_then(|ext| ext.to_str()) != Some("geojson") {
+                continue;
+            }
+
+            let (Some(name), Some(stem)) = (
+                path.file_name().and_then(|name| name.to_str()),
+                path.file_stem().and_then(|stem| stem.to_str()),
+            ) else { continue; };
+
+            if let Some(year) = parse_year(stem) {
+                layers.push((year, format!("features/{name}")));
+            }
+        }
+    }
+
+    layers.sort();
+
+    let mut catalog = String::from("pub const FEATURES: &[crate::FeatureLayer<'static>] = &[\n");
+
+    for (year, path) in layers {
+        let label = format_year(year);
+
+        writeln!(
+            catalog, 
+            "    crate::FeatureLayer {{ year: {year}, path: {path:?}, label: {label:?} }},"
+        ).map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+    }
+
+    catalog.push_str("];\n");
+
+    let out_dir = env::var("OUT_DIR")
+        .map_err(|_| io::Error::from(io::ErrorKind::NotFound))?;
+
+    fs::write(path::Path::new(&out_dir).join("catalog.rs"), catalog)?;
+
+    let features_dir = features_dir
+        .to_str()
+        .ok_or(io::Error::from(io::ErrorKind::InvalidData))?;
+
+    println!("cargo:rerun-if-changed={features_dir}");
+
+    Ok(())
+}
+
 fn set_workspace_root(root: path::PathBuf) -> io::Result<()> {
     let root = root
         .to_str()
@@ -32,6 +107,13 @@ fn main() -> io::Result<()> {
         .ok_or(io::Error::from(io::ErrorKind::NotFound))?;
 
     build_assets(root.to_path_buf())?;
+
+    build_feature_catalog(root.to_path_buf())?;
+
+    // emitting rerun-if-changed above disables cargo's default of watching the package
+    // so the asset directory and this script have to be listed explicitly
+    println!("cargo:rerun-if-changed={}", root.join("assets").display());
+    println!("cargo:rerun-if-changed=build.rs");
     
     #[cfg(not(target_arch = "wasm32"))]
     set_workspace_root(root.to_path_buf())?;
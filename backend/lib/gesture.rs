@@ -0,0 +1,71 @@
+// translates raw touches into crate::AppEvent
+// two fingers moving apart or together are reported as a pinch
+#[derive(Default)]
+pub struct Touches {
+    active: Vec<(u64, crate::Position)>,
+    // distance between the two fingers of a pinch
+    span: Option<f32>,
+}
+
+impl Touches {
+    pub fn handle(
+        &mut self,
+        touch: winit::event::Touch,
+        curr: &mut Vec<crate::AppEvent>,
+    ) {
+        use winit::event::TouchPhase;
+
+        let winit::event::Touch { id, phase, location, .. } = touch;
+
+        let cursor = crate::Position::from(location.cast::<f32>());
+
+        match phase {
+            TouchPhase::Started => {
+                self.active.push((id, cursor));
+
+                curr.push(crate::AppEvent::TouchStart { id, cursor });
+            },
+            TouchPhase::Moved => {
+                if let Some((_, temp)) = self.active.iter_mut().find(|(temp, _)| *temp == id) {
+                    *temp = cursor;
+                }
+
+                curr.push(crate::AppEvent::TouchMove { id, cursor });
+
+                if let (Some(span), Some((span_curr, center))) = (self.span, self.pinch()) {
+                    if span > 0. {
+                        curr.push(crate::AppEvent::Pinch { scale: span_curr / span, center });
+                    }
+
+                    self.span = Some(span_curr);
+                }
+
+                return;
+            },
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.active.retain(|(temp, _)| *temp != id);
+
+                curr.push(crate::AppEvent::TouchEnd { id, cursor });
+            },
+        }
+
+        // the pinch restarts whenever a finger is added or lifted
+        self.span = self.pinch().map(|(span, _)| span);
+    }
+
+    // span and center of the active pinch
+    fn pinch(&self) -> Option<(f32, crate::Position)> {
+        match self.active.as_slice() {
+            [(_, a), (_, b)] => {
+                let span = ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt();
+
+                let center = crate::Position {
+                    x: (a.x + b.x) * 0.5,
+                    y: (a.y + b.y) * 0.5,
+                };
+
+                Some((span, center))
+            }, _ => None,
+        }
+    }
+}
@@ -25,6 +25,11 @@ pub mod wgpu {
     pub use wgpu::*;
 }
 
+// wall clock timestamps that also work in the browser
+pub mod chrono {
+    pub use chrono::*;
+}
+
 #[cfg(target_arch = "wasm32")]
 pub mod web {
     pub mod wasm_bindgen {
@@ -34,6 +39,24 @@ pub mod web {
     pub mod wasm_bindgen_futures {
         pub use wasm_bindgen_futures::*;
     }
+
+    // resolves on a later turn of the browser's event loop
+    // long running tasks await it to give way to input and rendering
+    pub async fn yield_now() {
+        use ::wasm_bindgen_futures::{js_sys, JsFuture};
+
+        let promise = js_sys::Promise::new(&mut |resolve, _| {
+            let scheduled = web_sys::window()
+                .map(|window| window.set_timeout_with_callback(&resolve).is_ok());
+
+            // resolve straight away rather than never
+            if scheduled != Some(true) {
+                let _ = resolve.call0(&::wasm_bindgen::JsValue::NULL);
+            }
+        });
+
+        let _ = JsFuture::from(promise).await;
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -43,6 +66,13 @@ pub mod native {
     }
 }
 
+// feature sets discovered under `features/` at build time
+// sorted chronologically
+pub mod catalog {
+    include!(concat!(env!("OUT_DIR"), "/catalog.rs"));
+}
+
+mod gesture;
 mod state;
 
 use std::error;
@@ -76,10 +106,17 @@ impl From<winit::dpi::PhysicalSize<u32>> for Size {
 #[derive(Debug)]
 pub enum AppEvent {
     Key {code: event::KeyCode, state: event::ElementState },
+    Character(char),
     Mouse { button: event::MouseButton, state: event::ElementState, cursor: Position },
     MouseScroll { delta: f32, cursor: Position },
     MouseScrollStopped,
     MouseMotion { x: f32, y: f32 },
+    CursorMoved { cursor: Position },
+    TouchStart { id: u64, cursor: Position },
+    TouchMove { id: u64, cursor: Position },
+    TouchEnd { id: u64, cursor: Position },
+    // scale is the ratio of the current span between two fingers to the previous one
+    Pinch { scale: f32, center: Position },
     Resized(Size)
 }
 
@@ -98,6 +135,7 @@ pub trait App {
     fn update(
         &mut self, 
         device: &wgpu::Device, queue: &wgpu::Queue,
+        assets: Assets,
         bytes: &[u8],
         asset_path: &str,
     ) -> Result<(), Self::UpdateError>;
@@ -114,6 +152,15 @@ pub trait App {
         assets: Assets, 
         event: AppEvent,
     ) -> bool;
+
+    // called after each frame with the time since the previous frame in seconds
+    // frames are drawn continuously for as long as this returns true
+    fn tick(
+        &mut self,
+        _device: &wgpu::Device, _queue: &wgpu::Queue,
+        _assets: Assets,
+        _dt: f32,
+    ) -> bool { false }
 }
 
 pub trait AppConfig: Copy {
@@ -199,7 +246,17 @@ pub async fn start<C, A>(config: C) -> Result<(), String>
                     let _ = err_inner.get_or_init(|| e);
 
                     event_target.exit();
+
+                    return;
                 }
+
+                let dt = state.frame_delta();
+
+                let assets = Assets { proxy: proxy.clone(), loading };
+
+                let animating = app.tick(&state.device, &state.queue, assets, dt);
+
+                state.set_animating(animating, event_target);
             },
             Event::UserEvent(req) => {
                 match req {
@@ -210,13 +267,16 @@ pub async fn start<C, A>(config: C) -> Result<(), String>
                         #[cfg(feature = "logging")]
                         log::debug!("finished loading asset [{}]", &path);
 
-                        if let Err(e) = app.update(&state.device, &state.queue, &bytes, &path) {
+                        let assets = Assets { proxy: proxy.clone(), loading };
+                        if let Err(e) = app.update(&state.device, &state.queue, assets, &bytes, &path) {
                             let _ = err_inner.get_or_init(|| Into::<anyhow::Error>::into(e));
         
                             event_target.exit();
                         }
                     },
                     Request::Failed => loading = false,
+                    // the redraw below gives App::tick a chance to pick up the finished work
+                    Request::Wake => { /*  */ },
                 }
 
                 state.window.request_redraw();
@@ -224,9 +284,16 @@ pub async fn start<C, A>(config: C) -> Result<(), String>
             event => match state.run(event, event_target) {
                 Ok(events) => {
                     for event in events {
+                        let escape = matches!(event, AppEvent::Key {
+                            code: event::KeyCode::Escape,
+                            state: event::ElementState::Pressed,
+                        });
+
                         let assets = Assets { proxy: proxy.clone(), loading };
                         if app.handle_event(&state.device, &state.queue, assets, event) {
                             state.window.request_redraw();
+                        } else if escape {
+                            event_target.exit();
                         }
                     }
                 },
@@ -259,10 +326,38 @@ pub struct AssetRef<'a> {
     pub locator: AssetLocator,
 }
 
+#[derive(Clone, Copy)]
+#[derive(Debug)]
+pub struct FeatureLayer<'a> {
+    // years before the common era are negative
+    pub year: i32,
+    pub path: &'a str,
+    // "323 BC", "1715"
+    pub label: &'a str,
+}
+
+impl<'a> FeatureLayer<'a> {
+    pub fn asset(self) -> AssetRef<'a> {
+        AssetRef { path: self.path, locator: AssetLocator::Local }
+    }
+}
+
 pub enum Request {
     Loading,
     Fulfilled { path: String, bytes: Vec<u8> }, 
     Failed,
+    Wake,
+}
+
+// lets work running away from the event loop ask for a frame once it's done
+#[derive(Clone)]
+pub struct Waker(winit::event_loop::EventLoopProxy<Request>);
+
+impl Waker {
+    pub fn wake(&self) {
+        // the event loop only goes away with the app
+        let _ = self.0.send_event(Request::Wake);
+    }
 }
 
 pub struct Assets {
@@ -289,6 +384,10 @@ impl Assets {
         }).get(path).copied().ok_or(Error::from(ErrorKind::NotFound))
     }
 
+    pub fn waker(&self) -> Waker {
+        Waker(self.proxy.clone())
+    }
+
     pub fn request(&self, aref: AssetRef<'_>) -> std::io::Result<()> {
         let Self { proxy, loading } = self;
 
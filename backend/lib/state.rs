@@ -36,6 +36,9 @@ pub struct State<'a> {
     pub surface_config: wgpu::SurfaceConfiguration,
     pub cursor: Option<winit::dpi::PhysicalPosition<f32>>,
     pub scroll_state: Option<chrono::DateTime<chrono::Local>>,
+    pub last_frame: Option<chrono::DateTime<chrono::Local>>,
+    pub animating: bool,
+    pub touches: crate::gesture::Touches,
 }
 
 impl<'a> State<'a> {
@@ -43,6 +46,11 @@ impl<'a> State<'a> {
     // crate::AppEvent::MouseScrollStopped
     const SCROLL_THRESHOLD: f32 = 200.;
 
+    // frame gaps longer than this (in seconds) are treated as a single frame
+    // so animations don't jump after the event loop has been idle
+    const FRAME_THRESHOLD: f32 = 0.1;
+    const FRAME_FALLBACK: f32 = 1. / 60.;
+
     pub async fn new(
         event_loop: &winit::event_loop::EventLoop<crate::Request>,
         surface_format: wgpu::TextureFormat,
@@ -153,6 +161,9 @@ impl<'a> State<'a> {
             surface_config,
             cursor: None,
             scroll_state: None,
+            last_frame: None,
+            animating: false,
+            touches: crate::gesture::Touches::default(),
         })
     }
 
@@ -178,47 +189,27 @@ impl<'a> State<'a> {
         event: winit::event::Event<crate::Request>,
         event_target: &winit::event_loop::EventLoopWindowTarget<crate::Request>,
     ) -> anyhow::Result<Vec<crate::AppEvent>> {
-        use winit::event::{Event, WindowEvent, KeyEvent, ElementState};
-
-        use winit::keyboard::{Key, NamedKey};
+        use winit::event::{Event, WindowEvent};
 
         let mut curr = Vec::with_capacity(2);
 
         match event {
+            // escape is passed on to the app, which closes the window if it goes unused
             Event::WindowEvent { 
                 window_id, 
-                event: WindowEvent::CloseRequested | WindowEvent::KeyboardInput {
-                    event: KeyEvent {
-                        state: ElementState::Pressed,
-                        logical_key: Key::Named(NamedKey::Escape), ..
-                    }, ..
-                }
+                event: WindowEvent::CloseRequested,
             } if window_id == self.window.id() => event_target.exit(),
             Event::WindowEvent { 
                 event: WindowEvent::Resized(physical_size), 
                 window_id, .. 
             } if window_id == self.window.id() => {
-                let Self {
-                    window,
-                    required_limits,
-                    device,
-                    surface_config, 
-                    surface, ..
-                } = self;
-
-                configure_surface_resolution(
-                    surface_config, 
-                    required_limits.clone(), 
-                    physical_size
-                );
+                self.resize(physical_size);
 
-                surface.configure(device, surface_config);
-
-                window.request_redraw();
+                self.window.request_redraw();
 
                 let size = crate::Size {
-                    width: surface_config.width,
-                    height: surface_config.height,
+                    width: self.surface_config.width,
+                    height: self.surface_config.height,
                 };
 
                 curr.push(crate::AppEvent::Resized(size));
@@ -227,7 +218,9 @@ impl<'a> State<'a> {
                 event: WindowEvent::CursorMoved { position, .. }, 
                 window_id, .. 
             } if window_id == self.window.id() => {
-                let _ = self.cursor.insert(position.cast());
+                let cursor = crate::Position::from(*self.cursor.insert(position.cast()));
+
+                curr.push(crate::AppEvent::CursorMoved { cursor });
             },
             Event::WindowEvent { 
                 event: WindowEvent::CursorLeft { .. }, 
@@ -239,11 +232,17 @@ impl<'a> State<'a> {
                 event: winit::event::WindowEvent::KeyboardInput { 
                     event: winit::event::KeyEvent {
                         physical_key: winit::keyboard::PhysicalKey::Code(code),
-                        state, ..
+                        state, 
+                        text, ..
                     }, .. 
                 }, window_id, .. 
             } if window_id == self.window.id() => {
                 curr.push(crate::AppEvent::Key { code, state });
+
+                // text is only produced when a key is pressed
+                if let Some(text) = text {
+                    curr.extend(text.chars().map(crate::AppEvent::Character));
+                }
             },
             Event::WindowEvent { 
                 event: winit::event::WindowEvent::MouseInput { 
@@ -257,6 +256,12 @@ impl<'a> State<'a> {
                     curr.push(crate::AppEvent::Mouse { button, state, cursor });
                 }, None => { /*  */ },
             },
+            Event::WindowEvent { 
+                event: winit::event::WindowEvent::Touch(touch), 
+                window_id, .. 
+            } if window_id == self.window.id() => {
+                self.touches.handle(touch, &mut curr);
+            },
             Event::DeviceEvent {
                 event: winit::event::DeviceEvent::MouseMotion { 
                     delta: (x, y),
@@ -270,12 +275,12 @@ impl<'a> State<'a> {
                 }, ..
             } => match self.cursor {
                 Some(cursor) => {
-                    let delta = match delta {
+                    let delta = -match delta {
                         winit::event::MouseScrollDelta::LineDelta(_, y) => y,
                         winit::event::MouseScrollDelta::PixelDelta(
                             winit::dpi::PhysicalPosition { y: scroll, .. }
                         ) => (self.window.scale_factor() * scroll) as f32 / 270.,
-                    } * -1.;
+                    };
     
                     let cursor = crate::Position::from(cursor);
     
@@ -309,14 +314,58 @@ impl<'a> State<'a> {
         if let Some(crate::AppEvent::MouseScrollStopped) = curr.last() {
             self.scroll_state = None;
 
-            event_target.set_control_flow({
-                winit::event_loop::ControlFlow::Wait
-            });
+            // the event loop keeps polling if an animation is still running
+            if !self.animating {
+                event_target.set_control_flow({
+                    winit::event_loop::ControlFlow::Wait
+                });
+            }
         }
 
         Ok(curr)
     }
 
+    // seconds elapsed since the previous frame
+    pub fn frame_delta(&mut self) -> f32 {
+        let temp = chrono::Local::now();
+
+        let dt = match self.last_frame.replace(temp) {
+            Some(timestamp) => temp
+                .signed_duration_since(timestamp)
+                .num_microseconds()
+                .map_or(Self::FRAME_FALLBACK, |dt| dt as f32 / 1_000_000.),
+            None => Self::FRAME_FALLBACK,
+        };
+
+        if (0. ..=Self::FRAME_THRESHOLD).contains(&dt) { dt } else { Self::FRAME_FALLBACK }
+    }
+
+    // keeps the event loop redrawing while the app is animating
+    pub fn set_animating(
+        &mut self, 
+        animating: bool, 
+        event_target: &winit::event_loop::EventLoopWindowTarget<crate::Request>,
+    ) {
+        self.animating = animating;
+
+        if animating {
+            event_target.set_control_flow({
+                winit::event_loop::ControlFlow::Poll
+            });
+
+            self.window.request_redraw();
+        } else {
+            // the next animation shouldn't account for idle time
+            self.last_frame = None;
+
+            if self.scroll_state.is_none() {
+                event_target.set_control_flow({
+                    winit::event_loop::ControlFlow::Wait
+                });
+            }
+        }
+    }
+
     pub fn process_encoder<E, F>(&self, mut op: F) -> anyhow::Result<()> where 
         E: error::Error + Send + Sync + 'static, 
         F: FnMut(&mut wgpu::CommandEncoder, &wgpu::TextureView) -> Result<(), E> {